@@ -0,0 +1,195 @@
+use super::dictionary::DictionaryTrie;
+use super::game::{remove_tiles, RACK_SIZE};
+use super::gcg::{word_to_gcg, GcgAction, GcgGame};
+use super::leave::LeaveTable;
+use super::letter_bag::LetterBag;
+use super::util::{Letter, Word};
+use super::{ScrabbleBoard, ScrabbleSolution};
+use serde::Serialize;
+use std::cmp::Ordering;
+
+//Equities closer than this are counted as equal when ranking moves
+const EQUITY_TOLERANCE: f32 = 0.001;
+
+///
+/// How one move of a game compares with every play that was available for the same rack
+#[derive(Serialize)]
+pub struct TurnAnalysis {
+    //1 based, counting every move of the game record
+    pub move_number: usize,
+    pub player: usize,
+    pub rack: Word,
+    //The move as written in the game record, e.g. 8H DOG
+    pub played: String,
+    pub score: i32,
+    pub equity: f32,
+    //Where the move ranks by equity among the plays available, 1 for the best
+    pub rank: usize,
+    pub options: usize,
+    //The play with the most equity, if any play was available
+    pub best: Option<String>,
+    pub best_equity: f32,
+    //Compared with the highest scoring play
+    pub score_lost: i32,
+    pub equity_lost: f32,
+    //The highest scoring bingo, if one was available and not played
+    pub missed_bingo: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PlayerSummary {
+    pub name: String,
+    pub turns_analyzed: usize,
+    pub score_lost: i32,
+    pub equity_lost: f32,
+    pub missed_bingos: usize,
+}
+
+#[derive(Serialize)]
+pub struct GameAnalysis {
+    pub turns: Vec<TurnAnalysis>,
+    //In the order of the game record's players
+    pub players: Vec<PlayerSummary>,
+}
+
+impl GameAnalysis {
+    ///
+    /// The n turns that lost the most equity, worst first, leaving out turns that lost none
+    pub fn biggest_mistakes(&self, n: usize) -> Vec<&TurnAnalysis> {
+        let mut mistakes: Vec<&TurnAnalysis> = self.turns.iter()
+            .filter(|t| t.equity_lost > EQUITY_TOLERANCE)
+            .collect();
+        mistakes.sort_by(|a, b| b.equity_lost.partial_cmp(&a.equity_lost).unwrap_or(Ordering::Equal));
+        mistakes.truncate(n);
+        mistakes
+    }
+
+    pub fn missed_bingos(&self) -> Vec<&TurnAnalysis> {
+        self.turns.iter().filter(|t| t.missed_bingo.is_some()).collect()
+    }
+}
+
+fn describe(solution: &ScrabbleSolution) -> String {
+    format!("{} {}", solution.start_coord.notation(solution.direction), word_to_gcg(&solution.word))
+}
+
+fn leave_after(rack: &str, tiles: &[Letter]) -> Result<Word, String> {
+    let mut leave: Vec<Letter> = rack.bytes().collect();
+    remove_tiles(&mut leave, tiles)?;
+    leave.sort();
+    Ok(String::from_utf8(leave).unwrap())
+}
+
+///
+/// Replays a game record and compares each play, pass and exchange whose rack was recorded with
+/// all the plays available on that rack, valuing moves by score plus the leave's value in leaves.
+/// A play that was challenged off scores nothing and keeps the whole rack
+pub fn analyze(game: &GcgGame, dict: &DictionaryTrie, leaves: &LeaveTable) -> Result<GameAnalysis, String> {
+    let replayed = game.replay(dict)?;
+    let mut turns = Vec::new();
+    let mut board = ScrabbleBoard::empty_scrabble_board();
+
+    for (i, (m, turn)) in game.moves.iter().zip(replayed.iter()).enumerate() {
+        let board_before = std::mem::replace(&mut board, turn.board.clone());
+        if m.rack.is_empty() {
+            continue;
+        }
+        let withdrawn = game.moves.get(i + 1).map_or(false, |next| next.action == GcgAction::PhonyWithdrawn);
+        let (score, leave, tiles_placed) = match (&m.action, &turn.validation) {
+            (GcgAction::Play { .. }, Some(_)) if withdrawn => (0, m.rack.clone(), 0),
+            (GcgAction::Play { .. }, Some(validation)) => {
+                let tiles = validation.tiles_used();
+                (validation.score as i32, leave_after(&m.rack, &tiles)?, tiles.len())
+            },
+            (GcgAction::Pass, _) => (0, m.rack.clone(), 0),
+            //Only exchanges of known tiles leave something to value
+            (GcgAction::Exchange { tiles, .. }, _) if !tiles.is_empty() => (0, leave_after(&m.rack, tiles.as_bytes())?, 0),
+            _ => continue
+        };
+        let equity = score as f32 + leaves.value(&leave);
+
+        let solutions = board_before.find_all_valid_words(&LetterBag::from_string(&m.rack), dict);
+        let best = solutions.iter()
+            .max_by(|a, b| a.equity(leaves).partial_cmp(&b.equity(leaves)).unwrap_or(Ordering::Equal));
+        let best_equity = best.map_or(equity, |b| b.equity(leaves).max(equity));
+        let best_score = solutions.iter().map(|s| s.score as i32).max().unwrap_or(0);
+        let rank = 1 + solutions.iter().filter(|s| s.equity(leaves) > equity + EQUITY_TOLERANCE).count();
+        let missed_bingo = if tiles_placed < RACK_SIZE {
+            solutions.iter()
+                .filter(|s| s.tiles_placed as usize >= RACK_SIZE)
+                .max_by_key(|s| s.score)
+                .map(describe)
+        } else {
+            None
+        };
+
+        turns.push(TurnAnalysis {
+            move_number: i + 1,
+            player: turn.player,
+            rack: m.rack.clone(),
+            played: m.action.to_string(),
+            score,
+            equity,
+            rank,
+            options: solutions.len(),
+            best: best.map(describe),
+            best_equity,
+            score_lost: (best_score - score).max(0),
+            equity_lost: best_equity - equity,
+            missed_bingo,
+        });
+    }
+
+    let players = game.players.iter()
+        .enumerate()
+        .map(|(player, p)| {
+            let player_turns: Vec<&TurnAnalysis> = turns.iter().filter(|t| t.player == player).collect();
+            PlayerSummary {
+                name: p.full_name.clone(),
+                turns_analyzed: player_turns.len(),
+                score_lost: player_turns.iter().map(|t| t.score_lost).sum(),
+                equity_lost: player_turns.iter().map(|t| t.equity_lost).sum(),
+                missed_bingos: player_turns.iter().filter(|t| t.missed_bingo.is_some()).count(),
+            }
+        })
+        .collect();
+    Ok(GameAnalysis { turns, players })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_analysis() {
+        let dict = DictionaryTrie::from_word_list("dog dogs god gods dogstar");
+        let game = GcgGame::from_gcg("\
+#player1 ann Ann
+#player2 bob Bob
+>ann: ADGORST 8H DOG +10 10
+>bob: EEIIOUU - +0 0
+>ann: EEIISUU 8H ...S +6 16
+").unwrap();
+        let analysis = analyze(&game, &dict, &LeaveTable::new()).unwrap();
+        assert_eq!(analysis.turns.len(), 3);
+
+        let dog = &analysis.turns[0];
+        assert_eq!(dog.played, "8H DOG");
+        assert_eq!(dog.equity, 10.0);
+        assert!(dog.best.as_ref().unwrap().ends_with(" DOGSTAR"));
+        assert!(dog.missed_bingo.as_ref().unwrap().ends_with(" DOGSTAR"));
+        assert!(dog.rank > 1);
+        assert_eq!(dog.equity_lost, dog.best_equity - 10.0);
+        assert_eq!(dog.score_lost as f32, dog.equity_lost);
+
+        //Nothing to play, so passing lost nothing
+        let pass = &analysis.turns[1];
+        assert_eq!((pass.options, pass.rank, pass.equity_lost), (0, 1, 0.0));
+
+        assert_eq!(analysis.turns[2].rank, 1);
+        assert_eq!(analysis.players[0].missed_bingos, 1);
+        assert_eq!(analysis.players[1].equity_lost, 0.0);
+        assert_eq!(analysis.biggest_mistakes(5).len(), 1);
+        assert_eq!(analysis.missed_bingos()[0].move_number, 1);
+    }
+}
@@ -0,0 +1,100 @@
+use std::env;
+use std::process;
+
+use ScrabbleSolver::{LeaveTable, LexiconSet};
+use ScrabbleSolver::analysis::{self, GameAnalysis, TurnAnalysis};
+use ScrabbleSolver::gcg::GcgGame;
+
+const BIGGEST_MISTAKES_COUNT: usize = 5;
+
+const USAGE: &str = "\
+usage: analyze [--lexicon NAME] [--leaves PATH] [--format text|json] GAME.gcg
+    compares every move of a GCG game record with the plays available to its rack,
+    valuing moves by score plus the value of the leave (single tile values by default)";
+
+fn print_turn(game: &GcgGame, turn: &TurnAnalysis) {
+    let best = match &turn.best {
+        Some(best) => format!("best {} ({:.1})", best, turn.best_equity),
+        None => String::from("no plays")
+    };
+    println!("{:>3} {:<10} {:<7} {:<16} {:>4} ({:.1}) ranked {} of {}, {}, lost {:.1}",
+             turn.move_number,
+             game.players[turn.player].nickname,
+             turn.rack.to_ascii_uppercase().replace('*', "?"),
+             turn.played,
+             turn.score,
+             turn.equity,
+             turn.rank,
+             turn.options,
+             best,
+             turn.equity_lost);
+}
+
+fn print_report(analysis: &GameAnalysis, game: &GcgGame) {
+    println!("Moves:");
+    for turn in analysis.turns.iter() {
+        print_turn(game, turn);
+    }
+
+    println!("Missed bingos:");
+    for turn in analysis.missed_bingos() {
+        println!("{:>3} {:<10} {}", turn.move_number, game.players[turn.player].nickname,
+                 turn.missed_bingo.as_deref().unwrap_or(""));
+    }
+
+    println!("Biggest mistakes:");
+    for turn in analysis.biggest_mistakes(BIGGEST_MISTAKES_COUNT) {
+        print_turn(game, turn);
+    }
+
+    println!("Totals:");
+    for player in analysis.players.iter() {
+        println!("{}: {:.1} equity and {} points lost over {} moves, {} bingos missed",
+                 player.name, player.equity_lost, player.score_lost, player.turns_analyzed, player.missed_bingos);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut lexicon_name = None;
+    let mut leaves_path = None;
+    let mut json = false;
+    let mut game_path = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lexicon" => lexicon_name = Some(args.next().ok_or("--lexicon needs a name")?),
+            "--leaves" => leaves_path = Some(args.next().ok_or("--leaves needs a path")?),
+            "--format" => json = match args.next().as_ref().map(|s| s.as_str()) {
+                Some("text") => false,
+                Some("json") => true,
+                _ => return Err(String::from("--format needs text or json"))
+            },
+            _ => game_path = Some(arg)
+        }
+    }
+
+    let game = GcgGame::from_file(&game_path.ok_or("No game given")?)?;
+    let lexicons = LexiconSet::from_configured_file()?;
+    let lexicon = lexicons.get(lexicon_name.as_ref().map(|s| s.as_str()))?;
+    let leaves = match leaves_path {
+        Some(path) => LeaveTable::from_file(&path)?,
+        None => LeaveTable::single_tiles()
+    };
+
+    let analysis = analysis::analyze(&game, &lexicon.dict, &leaves)?;
+    if json {
+        println!("{}", serde_json::to_string(&analysis).map_err(|e| e.to_string())?);
+    } else {
+        print_report(&analysis, &game);
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(env::args().skip(1).collect()) {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
@@ -6,6 +6,8 @@ use ScrabbleSolver::{DictionaryTrie,
                      Coord,
                      Direction,
                      LetterBag,
+                     SolveQuery,
+                     SolutionRanking,
                      print_top_solutions};
 
 fn main() {
@@ -22,4 +24,26 @@ fn main() {
         println!("{} solutions found", solutions.len());
         println!("Took {} seconds", duration);
     }
+
+    for _i in 1..5 {
+        let now = Instant::now();
+        let page = board.find_top_valid_words(&LetterBag::from_string("**saebd"),
+                                              &dict,
+                                              &SolveQuery::default(),
+                                              SolutionRanking::Score,
+                                              0,
+                                              10);
+        let duration = (Instant::now() - now).as_seconds_f32();
+        println!("Top {} of {} solutions kept", page.solutions.len(), page.total);
+        println!("Took {} seconds", duration);
+    }
+
+    let collapsed_query = SolveQuery { collapse_blanks: true, ..SolveQuery::default() };
+    let page = board.find_top_valid_words(&LetterBag::from_string("**saebd"),
+                                          &dict,
+                                          &collapsed_query,
+                                          SolutionRanking::Score,
+                                          0,
+                                          10);
+    println!("{} solutions with the best blank designations", page.total);
 }
\ No newline at end of file
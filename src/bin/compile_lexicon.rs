@@ -0,0 +1,39 @@
+extern crate time;
+
+use std::env;
+use std::path::Path;
+use std::process;
+use time::Instant;
+
+use ScrabbleSolver::{load_lexicon_source, DictionaryTrie};
+
+const USAGE: &str = "\
+usage: compile_lexicon SOURCE OUTPUT.trie
+    SOURCE is builtin:ospd, builtin:2019 or the path of a whitespace separated word list, as in
+    lexicons.txt";
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (source, output) = match args {
+        [source, output] => (source, output),
+        _ => return Err(String::from("Expected a source and an output file"))
+    };
+
+    let dict = load_lexicon_source(source, Path::new("."))?;
+    dict.write_binary_file(output)?;
+
+    //Reading the file back checks it and shows how long loading it takes
+    let now = Instant::now();
+    let reread = DictionaryTrie::from_binary_file(output)?;
+    let duration = (Instant::now() - now).as_seconds_f32();
+    println!("Wrote {} words to {}, which loads in {} seconds", reread.word_count(), output, duration);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
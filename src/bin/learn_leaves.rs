@@ -0,0 +1,102 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+
+use ScrabbleSolver::{LeaveTable, LexiconSet};
+use ScrabbleSolver::leave_learning::{self, LeaveSamples};
+use ScrabbleSolver::tournament::Bot;
+
+const DEFAULT_GAMES: usize = 10000;
+const DEFAULT_GENERATIONS: usize = 3;
+const DEFAULT_THREADS: usize = 4;
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_MIN_SAMPLES: usize = 50;
+
+const USAGE: &str = "\
+usage: learn_leaves [--lexicon NAME] [--games N] [--generations N] [--threads N] [--seed N]
+                    [--min-samples N] [--start LEAVES] OUTPUT
+    learns a leave table for a lexicon from games between equity bots, valuing each leave by how
+    much more than average its player gained on the spread by their next turn, i.e. their next
+    score less the opponent's score in between. Each generation (default 3)
+    plays N games (default 10000) on N threads (default 4) with the table learned by the one
+    before, starting from a leave table file or single tile values, and writes the table to
+    OUTPUT. Leaves seen fewer than N times (default 50) are left out, so they are valued as the
+    sum of their single tiles";
+
+fn parse_number<T: std::str::FromStr>(arg: Option<String>, flag: &str) -> Result<T, String> {
+    arg.and_then(|n| n.parse().ok()).ok_or_else(|| format!("{} needs a number", flag))
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut lexicon_name = None;
+    let mut games = DEFAULT_GAMES;
+    let mut generations = DEFAULT_GENERATIONS;
+    let mut threads = DEFAULT_THREADS;
+    let mut seed = DEFAULT_SEED;
+    let mut min_samples = DEFAULT_MIN_SAMPLES;
+    let mut start_path = None;
+    let mut output_path = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lexicon" => lexicon_name = Some(args.next().ok_or("--lexicon needs a name")?),
+            "--games" => games = parse_number(args.next(), "--games")?,
+            "--generations" => generations = parse_number(args.next(), "--generations")?,
+            "--threads" => threads = parse_number(args.next(), "--threads")?,
+            "--seed" => seed = parse_number(args.next(), "--seed")?,
+            "--min-samples" => min_samples = parse_number(args.next(), "--min-samples")?,
+            "--start" => start_path = Some(args.next().ok_or("--start needs a path")?),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ if output_path.is_some() => return Err(format!("Unexpected argument {}, the output file is already given", arg)),
+            _ => output_path = Some(arg)
+        }
+    }
+    let output_path = output_path.ok_or("No output file given")?;
+    if threads == 0 {
+        return Err(String::from("--threads needs at least 1"));
+    }
+
+    let lexicons = LexiconSet::from_configured_file()?;
+    let dict = Arc::clone(&lexicons.get(lexicon_name.as_ref().map(|s| s.as_str()))?.dict);
+    let mut leaves = match start_path {
+        Some(path) => LeaveTable::from_file(&path)?,
+        None => LeaveTable::single_tiles()
+    };
+
+    for generation in 0..generations {
+        let bot = Arc::new(Bot::Equity { leaves });
+        //Every generation plays new games
+        let generation_seed = seed.wrapping_add((generation * games) as u64);
+        let handles: Vec<thread::JoinHandle<Result<LeaveSamples, String>>> = (0..threads)
+            .map(|first| {
+                let dict = Arc::clone(&dict);
+                let bot = Arc::clone(&bot);
+                thread::spawn(move || leave_learning::sample_games(dict, &bot, generation_seed, games, first, threads))
+            })
+            .collect();
+
+        let mut samples = LeaveSamples::new();
+        for handle in handles {
+            let thread_samples = handle.join().map_err(|_| String::from("A self-play thread panicked"))??;
+            samples.merge(&thread_samples);
+        }
+
+        leaves = samples.to_table(min_samples);
+        fs::write(&output_path, leaves.to_spec())
+            .map_err(|e| format!("Could not write leave table {}: {}", output_path, e))?;
+        println!("Generation {}: {} leaves from {} games, {} valued",
+                 generation + 1, samples.len(), games, leaves.len());
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(env::args().skip(1).collect()) {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
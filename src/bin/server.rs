@@ -5,9 +5,13 @@
 extern crate regex;
 
 
-use ScrabbleSolver::{DictionaryTrie, ScrabbleBoard, Coord, Direction, LetterBag, print_top_solutions, ScrabbleSolution};
+use ScrabbleSolver::{DictionaryTrie, Hooks, ScrabbleBoard, Coord, Direction, LetterBag, print_top_solutions, ScrabbleSolution, SolutionSink, Lexicon, LexiconSet, SolveQuery, SolutionRanking, SolutionsPage, LeaveTable, WordPattern, TileDistribution};
 use rocket::State;
-use rocket::http::Method;
+use rocket::http::{Method, ContentType};
+use rocket::request::Form;
+use rocket::response::Stream;
+use rocket::response::content::Content;
+use rocket::response::status::{BadRequest, NotFound};
 use rocket_contrib::json::Json;
 use rocket_contrib::serve::StaticFiles;
 use regex::Regex;
@@ -15,12 +19,67 @@ use serde::Serialize;
 use std::fmt::Display;
 use std::error::Error;
 use std::ops::Try;
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread;
 
+//How many solutions a streaming search may get ahead of the client
+const STREAM_CHANNEL_BOUND: usize = 256;
 
-//http://localhost:8000/is_word?word=dog
-#[get("/is_word?<word>")]
-fn is_word(dict_trie: State<DictionaryTrie>, word:String) -> String {
-    format!("{}", dict_trie.inner().is_word_string(&word))
+
+#[derive(Serialize)]
+struct IsWordResponse {
+    word:String,
+    is_word:bool,
+    hooks:Hooks,
+    definition:Option<String>,
+}
+
+///
+/// Plain true or false by default, or with hooks=true a JSON object that also has the word's
+/// hooks and definition
+//http://localhost:8000/is_word?word=dog&lexicon=nwl2019&hooks=true
+#[get("/is_word?<word>&<lexicon>&<hooks>")]
+fn is_word(lexicons: State<LexiconSet>,
+           word:String,
+           lexicon:Option<String>,
+           hooks:Option<bool>) -> Result<Content<String>, NotFound<String>> {
+    let lexicon = lexicons.get(lexicon.as_deref()).map_err(NotFound)?;
+    let word = word.to_ascii_lowercase();
+    if !word.bytes().all(|l| l.is_ascii_lowercase()) {
+        return Err(NotFound(format!("{} is not made of letters", word)));
+    }
+    let is_word = lexicon.dict.is_word_string(&word);
+    if !hooks.unwrap_or(false) {
+        return Ok(Content(ContentType::Plain, format!("{}", is_word)));
+    }
+
+    let response = IsWordResponse {
+        is_word,
+        hooks: lexicon.dict.hooks(&word),
+        definition: lexicon.definitions.get(&word).map(String::from),
+        word,
+    };
+    //Strings and bools always serialize
+    Ok(Content(ContentType::JSON, serde_json::to_string(&response).unwrap()))
+}
+
+#[derive(Serialize)]
+struct LexiconInfo {
+    name:String,
+    word_count:usize,
+}
+
+//http://localhost:8000/lexicons
+#[get("/lexicons")]
+fn lexicons(lexicons: State<LexiconSet>) -> Json<Vec<LexiconInfo>> {
+    let infos = lexicons.iter()
+        .map(|l| LexiconInfo {name:l.name.clone(), word_count:l.dict.word_count()})
+        .collect();
+    Json(infos)
 }
 
 
@@ -62,37 +121,314 @@ fn boardspec_to_board(board_spec:&str) -> Result<ScrabbleBoard,String> {
     Result::Ok(board)
 }
 
+///
+/// A solution with its position in standard notation (e.g. 8H, H8) alongside the numeric fields
+#[derive(Serialize)]
+struct SolutionJson {
+    #[serde(flatten)]
+    solution:ScrabbleSolution,
+    position:String,
+}
+
+impl From<ScrabbleSolution> for SolutionJson {
+    fn from(solution: ScrabbleSolution) -> SolutionJson {
+        let position = solution.start_coord.notation(solution.direction);
+        SolutionJson {solution, position}
+    }
+}
+
 #[derive(Serialize)]
 struct SolutionsResponse {
     error:Option<String>,
-    solutions:Vec<ScrabbleSolution>
-}
-
-//http://localhost:8000/solutions?board_letters=s&board_spec=7,5,d;7,6,o;7,7,g
-#[get("/solutions?<board_letters>&<board_spec>")]
-fn solutions(dict_trie: State<DictionaryTrie>,
-             board_letters:String,
-             board_spec:String) -> Json<SolutionsResponse> {
-    let letter_bag = LetterBag::from_string(&board_letters);
-    let board = boardspec_to_board(&board_spec);
-    match board {
-        Ok(board) => {
-            let mut solutions = board.find_all_valid_words(&letter_bag,
-                                                           dict_trie.inner());
-            solutions.sort_by_key(|s| -(s.score as i32));
-
-            Json(SolutionsResponse {error:None, solutions:solutions})
+    total:usize,
+    solutions:Vec<SolutionJson>,
+    //Definitions of the solutions' words which have one, by lowercase word
+    definitions:BTreeMap<String, String>,
+}
+
+#[derive(FromForm)]
+struct SolutionsQuery {
+    board_letters:String,
+    board_spec:String,
+    lexicon:Option<String>,
+    offset:Option<usize>,
+    limit:Option<usize>,
+    min_score:Option<u32>,
+    min_length:Option<usize>,
+    max_length:Option<usize>,
+    min_tiles:Option<u32>,
+    //Letters the word must contain
+    has:Option<String>,
+    //Square the word must cover, as row,col
+    through:Option<String>,
+    row:Option<i32>,
+    col:Option<i32>,
+    //r or d
+    dir:Option<String>,
+    //Regular expression the word must match
+    regex:Option<String>,
+    collapse_blanks:Option<bool>,
+    //"score" (the default) or "equity"
+    rank_by:Option<String>,
+}
+
+impl SolutionsQuery {
+    fn solve_query(&self) -> Result<SolveQuery, String> {
+        let mut solve_query = SolveQuery {
+            min_score: self.min_score.unwrap_or(0),
+            min_length: self.min_length,
+            max_length: self.max_length,
+            min_tiles_placed: self.min_tiles,
+            row: self.row,
+            col: self.col,
+            collapse_blanks: self.collapse_blanks.unwrap_or(false),
+            ..SolveQuery::default()
+        };
+
+        let text_restrictions = [("has", &self.has), ("through", &self.through),
+                                 ("dir", &self.dir), ("regex", &self.regex)];
+        for (key, value) in text_restrictions.iter() {
+            if let Some(value) = value {
+                solve_query.parse_restriction(&format!("{}={}", key, value))?;
+            }
+        }
+        Ok(solve_query)
+    }
+
+    fn ranking<'a>(&self, leaves: &'a LeaveTable) -> Result<SolutionRanking<'a>, String> {
+        match self.rank_by.as_deref() {
+            None | Some("score") => Ok(SolutionRanking::Score),
+            Some("equity") => Ok(SolutionRanking::Equity(leaves)),
+            Some(other) => Err(format!("Cannot rank solutions by {}", other))
+        }
+    }
+}
+
+fn find_solutions_page<'l>(lexicons: &'l LexiconSet,
+                           leaves: &LeaveTable,
+                           query: &SolutionsQuery) -> Result<(SolutionsPage, &'l Lexicon), String> {
+    let board = boardspec_to_board(&query.board_spec)?;
+    let lexicon = lexicons.get(query.lexicon.as_deref())?;
+    let solve_query = query.solve_query()?;
+    let ranking = query.ranking(leaves)?;
+    let letter_bag = LetterBag::from_string(&query.board_letters);
+    let page = board.find_top_valid_words(&letter_bag,
+                                          &lexicon.dict,
+                                          &solve_query,
+                                          ranking,
+                                          query.offset.unwrap_or(0),
+                                          query.limit.unwrap_or(usize::MAX));
+    Ok((page, lexicon))
+}
+
+//http://localhost:8000/solutions?board_letters=s&board_spec=7,5,d;7,6,o;7,7,g&lexicon=ospd&limit=20
+//http://localhost:8000/solutions?board_letters=saebdie&board_spec=7,5,d;7,6,o;7,7,g&min_tiles=7&through=7,7
+#[get("/solutions?<query..>")]
+fn solutions(lexicons: State<LexiconSet>,
+             leaves: State<LeaveTable>,
+             query: Form<SolutionsQuery>) -> Json<SolutionsResponse> {
+    match find_solutions_page(lexicons.inner(), leaves.inner(), &query) {
+        Ok((page, lexicon)) => {
+            let definitions = lexicon.definitions.of_words(page.solutions.iter().map(|s| s.word.as_str()));
+            let solutions = page.solutions.into_iter().map(SolutionJson::from).collect();
+            Json(SolutionsResponse {error:None, total:page.total, solutions, definitions})
+        },
+        Err(err_msg) => {
+            Json(SolutionsResponse {error:Some(err_msg), total:0, solutions:Vec::new(), definitions:BTreeMap::new()})
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PlusOneAnagrams {
+    letter:String,
+    words:Vec<String>,
+}
+
+#[derive(Serialize)]
+struct AnagramResponse {
+    error:Option<String>,
+    words:Vec<String>,
+    plus_one:Vec<PlusOneAnagrams>,
+    //Definitions of the words found which have one
+    definitions:BTreeMap<String, String>,
+}
+
+fn find_anagrams(lexicons: &LexiconSet,
+                 rack: &str,
+                 mode: Option<&str>,
+                 order: Option<&str>,
+                 lexicon: Option<&str>) -> Result<AnagramResponse, String> {
+    let lexicon = lexicons.get(lexicon)?;
+    let dict = &lexicon.dict;
+    let rack = LetterBag::from_rack(rack)?;
+    let sort_words: fn(&mut Vec<String>) = match order {
+        None | Some("alphabetical") => |_| (),
+        Some("probability") => |words| TileDistribution::standard().sort_by_probability(words),
+        Some(other) => return Err(format!("Unknown anagram order {}", other))
+    };
+
+    let mut response = AnagramResponse {error:None, words:Vec::new(), plus_one:Vec::new(), definitions:BTreeMap::new()};
+    match mode {
+        None | Some("anagram") => response.words = dict.anagrams(&rack),
+        Some("subanagram") => response.words = dict.subanagrams(&rack),
+        Some("plus_one") => {
+            response.plus_one = dict.rack_plus_one(&rack)
+                .into_iter()
+                .map(|(l, mut words)| {
+                    sort_words(&mut words);
+                    PlusOneAnagrams {letter:char::from(l).to_string(), words}
+                })
+                .collect();
         },
+        Some(other) => return Err(format!("Unknown anagram mode {}", other))
+    }
+    sort_words(&mut response.words);
+    let plus_one_words = response.plus_one.iter().flat_map(|p| p.words.iter());
+    response.definitions = lexicon.definitions.of_words(response.words.iter().chain(plus_one_words).map(|w| w.as_str()));
+    Ok(response)
+}
+
+///
+/// mode: anagram (the default) for words using the whole rack, subanagram for words using
+/// some of it, or plus_one for the letters that make anagrams when added to the rack
+/// order: alphabetical (the default) or probability, most likely to be drawn first
+//http://localhost:8000/anagram?rack=aeinst%3F&mode=anagram&order=probability
+#[get("/anagram?<rack>&<mode>&<order>&<lexicon>")]
+fn anagram(lexicons: State<LexiconSet>,
+           rack:String,
+           mode:Option<String>,
+           order:Option<String>,
+           lexicon:Option<String>) -> Json<AnagramResponse> {
+    match find_anagrams(lexicons.inner(), &rack, mode.as_deref(), order.as_deref(), lexicon.as_deref()) {
+        Ok(response) => Json(response),
         Err(err_msg) => {
-            Json(SolutionsResponse {error:Some(err_msg), solutions:Vec::new()})
+            Json(AnagramResponse {error:Some(err_msg), words:Vec::new(), plus_one:Vec::new(), definitions:BTreeMap::new()})
         }
     }
 }
 
+#[derive(Serialize)]
+struct PatternResponse {
+    error:Option<String>,
+    words:Vec<String>,
+}
+
+///
+/// pattern: see WordPattern, e.g. c?t* or *q[^u]* len=4-8 has=e
+//http://localhost:8000/pattern?pattern=c?t*%20len=5
+#[get("/pattern?<pattern>&<lexicon>")]
+fn pattern(lexicons: State<LexiconSet>,
+           pattern:String,
+           lexicon:Option<String>) -> Json<PatternResponse> {
+    let words = lexicons.get(lexicon.as_deref())
+        .and_then(|l| WordPattern::parse(&pattern).map(|p| l.dict.pattern_matches(&p)));
+    match words {
+        Ok(words) => Json(PatternResponse {error:None, words}),
+        Err(err_msg) => Json(PatternResponse {error:Some(err_msg), words:Vec::new()})
+    }
+}
+
+///
+/// Reads the lines sent by a search running on another thread,
+/// so they can be streamed out while the search is still going
+struct ChannelReader {
+    lines:Receiver<Vec<u8>>,
+    pending:Vec<u8>,
+    pos:usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.pending.len() {
+            match self.lines.recv() {
+                Ok(line) => {
+                    self.pending = line;
+                    self.pos = 0;
+                },
+                //The search finished and dropped its sender
+                Err(_) => return Ok(0)
+            }
+        }
+
+        let n = min(buf.len(), self.pending.len() - self.pos);
+        buf[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+///
+/// Sends the solutions found after the first offset down a channel as JSON lines, until limit
+/// of them are sent or the client goes away, which stops the search
+struct StreamSink {
+    sender:SyncSender<Vec<u8>>,
+    offset:usize,
+    limit:usize,
+    num_seen:usize,
+    disconnected:bool,
+}
+
+impl SolutionSink for StreamSink {
+    fn accept(&mut self, solution: ScrabbleSolution) {
+        self.num_seen += 1;
+        if self.num_seen <= self.offset || self.num_seen - self.offset > self.limit || self.disconnected {
+            return;
+        }
+        if let Ok(mut line) = serde_json::to_vec(&SolutionJson::from(solution)) {
+            line.push(b'\n');
+            //Fails once the client has gone away
+            self.disconnected = self.sender.send(line).is_err();
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.disconnected || self.num_seen.saturating_sub(self.offset) >= self.limit
+    }
+}
+
+///
+/// Streams solutions as newline delimited JSON in the order the search finds them (unsorted).
+/// Takes the same parameters as /solutions; offset and limit apply to the order found
+//http://localhost:8000/solutions/stream?board_letters=s&board_spec=7,5,d;7,6,o;7,7,g
+#[get("/solutions/stream?<query..>")]
+fn solutions_stream(lexicons: State<LexiconSet>,
+                    query: Form<SolutionsQuery>)
+                    -> Result<Content<Stream<ChannelReader>>, BadRequest<String>> {
+    let board = boardspec_to_board(&query.board_spec).map_err(|e| BadRequest(Some(e)))?;
+    let lexicon = lexicons.get(query.lexicon.as_deref()).map_err(|e| BadRequest(Some(e)))?;
+    let solve_query = query.solve_query().map_err(|e| BadRequest(Some(e)))?;
+    let dict = Arc::clone(&lexicon.dict);
+    let letter_bag = LetterBag::from_rack(&query.board_letters).map_err(|e| BadRequest(Some(e)))?;
+
+    let (sender, receiver) = sync_channel(STREAM_CHANNEL_BOUND);
+    let mut sink = StreamSink {
+        sender,
+        offset: query.offset.unwrap_or(0),
+        limit: query.limit.unwrap_or(usize::MAX),
+        num_seen: 0,
+        disconnected: false,
+    };
+    thread::spawn(move || {
+        board.for_each_queried_valid_word(&letter_bag, &dict, &solve_query, &mut sink);
+    });
+
+    let reader = ChannelReader {lines:receiver, pending:Vec::new(), pos:0};
+    Ok(Content(ContentType::new("application", "x-ndjson"), Stream::from(reader)))
+}
+
+fn load_lexicons() -> LexiconSet {
+    match LexiconSet::from_configured_file() {
+        Ok(lexicons) => lexicons,
+        Err(err_msg) => panic!("Could not load lexicons: {}", err_msg)
+    }
+}
+
 fn main() {
     rocket::ignite()
-        .manage(DictionaryTrie::from_scrabble_ospd())
-        .mount("/", routes![is_word, solutions])
+        .manage(load_lexicons())
+        .manage(LeaveTable::single_tiles())
+        .mount("/", routes![is_word, lexicons, solutions, solutions_stream, anagram, pattern])
         .mount("/", StaticFiles::from(concat!(env!("CARGO_MANIFEST_DIR"), "/frontend")))
         .launch();
 }
\ No newline at end of file
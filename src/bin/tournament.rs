@@ -0,0 +1,115 @@
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+
+use ScrabbleSolver::LexiconSet;
+use ScrabbleSolver::tournament::{self, Bot, MatchStats};
+
+const DEFAULT_GAMES: usize = 1000;
+const DEFAULT_THREADS: usize = 4;
+const DEFAULT_SEED: u64 = 1;
+
+const USAGE: &str = "\
+usage: tournament [--lexicon NAME] [--games N] [--threads N] [--seed N]
+                  [--sim-candidates N] [--sim-iterations N] BOT BOT
+    plays N games (default 1000) between two bots on N threads (default 4), each pair of games
+    sharing a seed with the bots swapping who moves first, and reports win rates with 95%
+    confidence intervals, average scores and spreads, bingos and game lengths
+bots:
+    greedy                  #the highest scoring play
+    equity[=LEAVES]         #the most score plus leave value, from a leave table file or
+                            # single tile values by default
+    sim[=LEAVES]            #the best of the top equity plays (default 5) after the opponent's
+                            # best reply to racks they might hold (default 20 of them)";
+
+fn parse_number<T: std::str::FromStr>(arg: Option<String>, flag: &str) -> Result<T, String> {
+    arg.and_then(|n| n.parse().ok()).ok_or_else(|| format!("{} needs a number", flag))
+}
+
+fn print_report(names: &[String; 2], stats: &MatchStats) {
+    println!("{} vs {}: {} games, {:.1} turns a game", names[0], names[1], stats.games, stats.average_turns());
+    println!("{:<24} {:>6} {:>6} {:>16} {:>10} {:>10} {:>12}",
+             "bot", "wins", "draws", "win rate", "avg score", "avg spread", "bingos/game");
+    for (name, bot) in names.iter().zip(stats.bots.iter()) {
+        println!("{:<24} {:>6} {:>6} {:>8.1}% ± {:>4.1}% {:>10.1} {:>10.1} {:>12.2}",
+                 name,
+                 bot.wins,
+                 bot.draws,
+                 100.0 * bot.win_rate(),
+                 100.0 * bot.win_rate_margin(),
+                 bot.average_score(),
+                 bot.average_spread(),
+                 bot.bingos_per_game());
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut lexicon_name = None;
+    let mut games = DEFAULT_GAMES;
+    let mut threads = DEFAULT_THREADS;
+    let mut seed = DEFAULT_SEED;
+    let mut sim_candidates = None;
+    let mut sim_iterations = None;
+    let mut specs = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lexicon" => lexicon_name = Some(args.next().ok_or("--lexicon needs a name")?),
+            "--games" => games = parse_number(args.next(), "--games")?,
+            "--threads" => threads = parse_number(args.next(), "--threads")?,
+            "--seed" => seed = parse_number(args.next(), "--seed")?,
+            "--sim-candidates" => sim_candidates = Some(parse_number(args.next(), "--sim-candidates")?),
+            "--sim-iterations" => sim_iterations = Some(parse_number(args.next(), "--sim-iterations")?),
+            _ => specs.push(arg)
+        }
+    }
+    if specs.len() != 2 {
+        return Err(String::from("Two bots are needed"));
+    }
+    if threads == 0 {
+        return Err(String::from("--threads needs at least 1"));
+    }
+
+    let mut bots = [Bot::parse(&specs[0])?, Bot::parse(&specs[1])?];
+    for bot in bots.iter_mut() {
+        if let Bot::Simulation { candidates, iterations, .. } = bot {
+            *candidates = sim_candidates.unwrap_or(*candidates);
+            *iterations = sim_iterations.unwrap_or(*iterations);
+        }
+    }
+    //Tell apart two bots of the same kind
+    let names = if specs[0] == specs[1] {
+        [format!("{} (1)", specs[0]), format!("{} (2)", specs[1])]
+    } else {
+        [specs[0].clone(), specs[1].clone()]
+    };
+
+    let lexicons = LexiconSet::from_configured_file()?;
+    let dict = Arc::clone(&lexicons.get(lexicon_name.as_ref().map(|s| s.as_str()))?.dict);
+    let bots = Arc::new(bots);
+    let handles: Vec<thread::JoinHandle<Result<MatchStats, String>>> = (0..threads)
+        .map(|first| {
+            let dict = Arc::clone(&dict);
+            let bots = Arc::clone(&bots);
+            thread::spawn(move || tournament::play_games(dict, &bots, seed, games, first, threads))
+        })
+        .collect();
+
+    let mut stats = MatchStats::default();
+    for handle in handles {
+        let thread_stats = handle.join().map_err(|_| String::from("A tournament thread panicked"))??;
+        stats.merge(&thread_stats);
+    }
+    print_report(&names, &stats);
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(env::args().skip(1).collect()) {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::process;
+
+use ScrabbleSolver::{DictionaryTrie, Lexicon, LexiconSet, wordlists};
+
+const DEFAULT_TOP_PROBABILITY_COUNT: usize = 100;
+
+const USAGE: &str = "\
+usage: wordlists [--lexicon NAME] [--format text|json] LIST
+lists:
+    length N               #all words of length N
+    q-without-u            #words with a Q but no U
+    jqxz                   #words with a J, Q, X or Z
+    vowel-heavy            #words with more vowels than consonants
+    top-probability N [M]  #the M (default 100) words of length N most likely to be drawn
+    unique-to OTHER        #words not in the lexicon named OTHER
+    diff OTHER             #words added and removed going from the lexicon to OTHER, by length";
+
+#[derive(Serialize)]
+struct WordListOutput<'a> {
+    lexicon: &'a str,
+    list: String,
+    count: usize,
+    words: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct DiffOutput<'a> {
+    lexicon: &'a str,
+    other: &'a str,
+    added: BTreeMap<usize, Vec<String>>,
+    removed: BTreeMap<usize, Vec<String>>,
+}
+
+fn print_by_length(heading: &str, words: &[String]) {
+    println!("{} ({}):", heading, words.len());
+    for (len, group) in wordlists::group_by_length(words) {
+        println!("{:>2}: {}", len, group.join(" "));
+    }
+}
+
+fn print_diff(lexicon: &Lexicon, other: &Lexicon, json: bool) -> Result<(), String> {
+    let diff = lexicon.dict.diff(&other.dict);
+    if json {
+        let output = DiffOutput {
+            lexicon: &lexicon.name,
+            other: &other.name,
+            added: wordlists::group_by_length(&diff.added),
+            removed: wordlists::group_by_length(&diff.removed),
+        };
+        println!("{}", serde_json::to_string(&output).map_err(|e| e.to_string())?);
+    } else {
+        println!("{} -> {}", lexicon.name, other.name);
+        print_by_length("Added", &diff.added);
+        print_by_length("Removed", &diff.removed);
+    }
+    Ok(())
+}
+
+fn parse_number(arg: Option<&String>) -> Result<usize, String> {
+    match arg {
+        Some(n) => n.parse().map_err(|_| format!("Expected a number, got {}", n)),
+        None => Err(String::from("Expected a number"))
+    }
+}
+
+fn generate_list(lexicons: &LexiconSet, dict: &DictionaryTrie, list: &[String]) -> Result<Vec<String>, String> {
+    match list.first().map(|s| s.as_str()) {
+        Some("length") => Ok(wordlists::words_of_length(dict, parse_number(list.get(1))?)),
+        Some("q-without-u") => Ok(wordlists::q_without_u(dict)),
+        Some("jqxz") => Ok(wordlists::with_jqxz(dict)),
+        Some("vowel-heavy") => Ok(wordlists::vowel_heavy(dict)),
+        Some("top-probability") => {
+            let len = parse_number(list.get(1))?;
+            let count = match list.get(2) {
+                Some(_) => parse_number(list.get(2))?,
+                None => DEFAULT_TOP_PROBABILITY_COUNT
+            };
+            Ok(wordlists::top_probability(dict, len, count))
+        },
+        Some("unique-to") => {
+            let other = lexicons.get(Some(list.get(1).ok_or("unique-to needs a lexicon name")?))?;
+            Ok(wordlists::unique_to(dict, &other.dict))
+        },
+        Some(other) => Err(format!("Unknown list {}", other)),
+        None => Err(String::from("No list given"))
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut lexicon_name = None;
+    let mut json = false;
+    let mut list = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lexicon" => lexicon_name = Some(args.next().ok_or("--lexicon needs a name")?),
+            "--format" => json = match args.next().as_ref().map(|s| s.as_str()) {
+                Some("text") => false,
+                Some("json") => true,
+                _ => return Err(String::from("--format needs text or json"))
+            },
+            _ => list.push(arg)
+        }
+    }
+
+    let lexicons = LexiconSet::from_configured_file()?;
+    let lexicon = lexicons.get(lexicon_name.as_ref().map(|s| s.as_str()))?;
+    if list.first().map(|s| s.as_str()) == Some("diff") {
+        let other = lexicons.get(Some(list.get(1).ok_or("diff needs a lexicon name")?))?;
+        return print_diff(lexicon, other, json);
+    }
+
+    let words = generate_list(&lexicons, &lexicon.dict, &list)?;
+
+    if json {
+        let output = WordListOutput { lexicon: &lexicon.name, list: list.join(" "), count: words.len(), words };
+        println!("{}", serde_json::to_string(&output).map_err(|e| e.to_string())?);
+    } else {
+        for word in words {
+            println!("{}", word);
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(env::args().skip(1).collect()) {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
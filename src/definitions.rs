@@ -0,0 +1,79 @@
+use super::util::Word;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+///
+/// Definitions of the words of a lexicon, looked up by lowercase word
+#[derive(Clone)]
+pub struct Definitions {
+    entries: HashMap<Word, String>,
+}
+
+impl Definitions {
+    pub fn new() -> Definitions {
+        Definitions { entries: HashMap::new() }
+    }
+
+    ///
+    /// spec: one word per line as <word><TAB><definition> e.g.
+    /// za	pizza
+    pub fn from_spec(spec: &str) -> Result<Definitions, String> {
+        let mut definitions = Definitions::new();
+        for line in spec.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '\t');
+            match (parts.next().map(|w| w.trim()), parts.next().map(|d| d.trim())) {
+                (Some(word), Some(definition)) if !word.is_empty() && !definition.is_empty() => {
+                    definitions.entries.insert(word.to_ascii_lowercase(), String::from(definition));
+                },
+                _ => return Err(format!("{} is not in <word><TAB><definition> format", line))
+            }
+        }
+        Ok(definitions)
+    }
+
+    pub fn from_file(path: &str) -> Result<Definitions, String> {
+        let spec = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read definitions {}: {}", path, e))?;
+        Self::from_spec(&spec)
+    }
+
+    pub fn get(&self, word: &str) -> Option<&str> {
+        self.entries.get(&word.to_ascii_lowercase()).map(|d| d.as_str())
+    }
+
+    ///
+    /// The definitions of those of words which have one, keyed by lowercase word
+    pub fn of_words<'w>(&self, words: impl Iterator<Item=&'w str>) -> BTreeMap<Word, String> {
+        words.filter_map(|w| self.get(w).map(|d| (w.to_ascii_lowercase(), String::from(d))))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definitions_lookup() {
+        let definitions = Definitions::from_spec("# comment\nza\tpizza\nQI\tvital force, in Chinese medicine\n\n").unwrap();
+        assert_eq!(definitions.len(), 2);
+        assert_eq!(definitions.get("ZA"), Some("pizza"));
+        assert_eq!(definitions.get("qi"), Some("vital force, in Chinese medicine"));
+        assert_eq!(definitions.get("xu"), None);
+
+        let defined = definitions.of_words(["Za", "xu"].iter().cloned());
+        assert_eq!(defined.get("za").map(|d| d.as_str()), Some("pizza"));
+        assert_eq!(defined.len(), 1);
+
+        assert!(Definitions::from_spec("za pizza").is_err());
+        assert!(Definitions::from_spec("za\t").is_err());
+    }
+}
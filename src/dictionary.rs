@@ -1,7 +1,29 @@
 use super::util::{Letter,Word};
+use super::letter_bag::LetterBag;
+use super::pattern::{WordPattern, PatternStates, count_missing_letters};
+use super::WILDCARD_LETTER;
+use std::collections::BTreeSet;
+use serde::Serialize;
+use std::fs;
 
 
 const ROOT_NODE_IDX: usize = 0;
+const IS_WORD_BIT: u32 = 1 << 26;
+
+//Binary trie files: magic, format version, node count, word count and a checksum of the
+// node records that follow, which are the records of DictionaryTrie, little endian
+const BINARY_MAGIC: &[u8; 8] = b"SCRBTRIE";
+const BINARY_VERSION: u32 = 1;
+const BINARY_HEADER_LEN: usize = 8 + 4 + 4 + 4 + 8;
+
+//FNV-1a, enough to catch truncated or corrupted files
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100_0000_01b3))
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> Option<u32> {
+    bytes.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
 
 fn letter_alpha_idx(c: Letter) -> u8 {
     if (b'a'..=b'z').contains(&c) {
@@ -28,18 +50,41 @@ fn utf8_to_alpha_indices(w: &Vec<u8>) -> Vec<u8> {
 }
 
 
+///
+/// Letters that can be put in front of (front) or after (back) a word to make another word
+#[derive(Serialize, PartialEq, Debug)]
+pub struct Hooks {
+    pub front: Word,
+    pub back: Word,
+}
+
+///
+/// Words added and removed between two lexicons, each in alphabetical order
+#[derive(Serialize, PartialEq, Debug)]
+pub struct LexiconDiff {
+    pub added: Vec<Word>,
+    pub removed: Vec<Word>,
+}
+
+///
+/// The nodes are packed one after another into records: a u32 with a bit per child letter
+/// and IS_WORD_BIT, followed by the offset of each child's record in alphabetical order.
+/// A child's record always comes after its parent's. Binary dictionary files hold exactly
+/// these records, so loading one is a single copy with no per-word work
 pub struct DictionaryTrie {
-    entries: Vec<DictionaryTrieNode>
+    records: Vec<u32>,
+    node_count: usize,
+    word_count: usize,
 }
 
 pub struct DictionaryTrieNodePtr<'a> {
     dict_trie: &'a DictionaryTrie,
-    node: &'a DictionaryTrieNode,
+    offset: usize,
 }
 
 impl<'a> DictionaryTrieNodePtr<'a> {
     pub fn is_word(&self) -> bool {
-        self.node.is_word
+        self.dict_trie.records[self.offset] & IS_WORD_BIT != 0
     }
 
     pub fn get_child(&self, c: Letter) -> Option<DictionaryTrieNodePtr<'a>> {
@@ -47,14 +92,16 @@ impl<'a> DictionaryTrieNodePtr<'a> {
     }
 
     pub fn get_child_idx(&self, alpha_idx: u8) -> Option<DictionaryTrieNodePtr<'a>> {
-        match self.node.get_child_idx(alpha_idx) {
-            None => None,
-            Some(new_idx) => {
-                let new_node = &self.dict_trie.entries[new_idx];
-                let new_ptr = DictionaryTrieNodePtr { dict_trie: self.dict_trie, node: new_node };
-                Some(new_ptr)
-            }
+        let records = &self.dict_trie.records;
+        let mask = records[self.offset];
+        let letter_bit = 1 << alpha_idx;
+        if mask & letter_bit == 0 {
+            return None;
         }
+        //Children are stored in alphabetical order, after the mask
+        let child_rank = (mask & (letter_bit - 1)).count_ones() as usize;
+        let child_offset = records[self.offset + 1 + child_rank] as usize;
+        Some(DictionaryTrieNodePtr { dict_trie: self.dict_trie, offset: child_offset })
     }
 
     pub fn dict_trie(&self) -> &'a DictionaryTrie {
@@ -65,18 +112,16 @@ impl<'a> DictionaryTrieNodePtr<'a> {
 
 impl DictionaryTrie {
     pub fn new() -> DictionaryTrie {
-        let mut entries = Vec::new();
-        entries.push(DictionaryTrieNode::new());
-        DictionaryTrie { entries: entries }
+        TrieBuilder::new().build()
     }
 
-    //
-    fn from_word_list(word_list:&str) -> DictionaryTrie {
-        let mut trie = DictionaryTrie::new();
+    //Builds a trie from whitespace separated words
+    pub fn from_word_list(word_list:&str) -> DictionaryTrie {
+        let mut builder = TrieBuilder::new();
         word_list.split_ascii_whitespace()
             .into_iter()
-            .for_each(|s| trie.add_word(s.to_owned()));
-        trie
+            .for_each(|s| builder.add_alpha_indices(&word_to_alpha_indices(&s.to_owned())));
+        builder.build()
     }
 
     pub fn from_scrabble_2019() -> DictionaryTrie {
@@ -89,32 +134,155 @@ impl DictionaryTrie {
         Self::from_word_list(scrabble_dict)
     }
 
+    //Loads a word list file with the same layout as the bundled dictionaries
+    pub fn from_file(path: &str) -> Result<DictionaryTrie, String> {
+        let word_list = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        if let Some(word) = word_list.split_ascii_whitespace().find(|w| !w.bytes().all(|b| b.is_ascii_alphabetic())) {
+            return Err(format!("{}: {} is not a word of letters", path, word));
+        }
+        Ok(Self::from_word_list(&word_list))
+    }
+
+    ///
+    /// The trie in the binary format read by from_bytes, which loads much faster than a word list
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut records = Vec::with_capacity(4 * self.records.len());
+        for record in self.records.iter() {
+            records.extend_from_slice(&record.to_le_bytes());
+        }
+
+        let mut bytes = Vec::with_capacity(BINARY_HEADER_LEN + records.len());
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.node_count as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.word_count as u32).to_le_bytes());
+        bytes.extend_from_slice(&checksum(&records).to_le_bytes());
+        bytes.extend(records);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<DictionaryTrie, String> {
+        if bytes.len() < BINARY_HEADER_LEN || &bytes[..8] != BINARY_MAGIC {
+            return Err(String::from("Not a binary dictionary file"));
+        }
+        let version = read_u32(bytes, 8).unwrap();
+        if version != BINARY_VERSION {
+            return Err(format!("Binary dictionary version {} is not supported, expected {}", version, BINARY_VERSION));
+        }
+        let node_count = read_u32(bytes, 12).unwrap() as usize;
+        let word_count = read_u32(bytes, 16).unwrap() as usize;
+        let mut stored_checksum = [0; 8];
+        stored_checksum.copy_from_slice(&bytes[20..28]);
+        let record_bytes = &bytes[BINARY_HEADER_LEN..];
+        if checksum(record_bytes) != u64::from_le_bytes(stored_checksum) {
+            return Err(String::from("Binary dictionary checksum does not match, the file is corrupt"));
+        }
+        if record_bytes.len() % 4 != 0 {
+            return Err(String::from("Binary dictionary records are corrupt"));
+        }
+
+        let records: Vec<u32> = record_bytes.chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let trie = DictionaryTrie { records, node_count, word_count };
+        if trie.has_valid_records() {
+            Ok(trie)
+        } else {
+            Err(String::from("Binary dictionary records are corrupt"))
+        }
+    }
+
+    //Whether the records can be walked safely: every child offset is the start of a record
+    // after its parent's, and there are node_count records
+    fn has_valid_records(&self) -> bool {
+        let mut is_record_start = vec![false; self.records.len()];
+        let mut offset = 0;
+        let mut nodes = 0;
+        while offset < self.records.len() {
+            let mask = self.records[offset];
+            if mask & !(IS_WORD_BIT | (IS_WORD_BIT - 1)) != 0 {
+                return false;
+            }
+            is_record_start[offset] = true;
+            offset += 1 + (mask & (IS_WORD_BIT - 1)).count_ones() as usize;
+            nodes += 1;
+        }
+        if offset != self.records.len() || nodes != self.node_count || nodes == 0 {
+            return false;
+        }
+
+        let mut offset = 0;
+        while offset < self.records.len() {
+            let num_children = (self.records[offset] & (IS_WORD_BIT - 1)).count_ones() as usize;
+            let children = &self.records[offset + 1..offset + 1 + num_children];
+            if !children.iter().all(|&child| child as usize > offset && is_record_start.get(child as usize).copied().unwrap_or(false)) {
+                return false;
+            }
+            offset += 1 + num_children;
+        }
+        true
+    }
+
+    pub fn write_binary_file(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_bytes()).map_err(|e| format!("Could not write {}: {}", path, e))
+    }
+
+    pub fn from_binary_file(path: &str) -> Result<DictionaryTrie, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
     pub fn add_word(&mut self, s: Word) {
         self.add_alpha_indices(word_to_alpha_indices(&s))
     }
 
+    ///
+    /// Repacks the whole trie, so adding more than a few words is better done with from_word_list
     pub fn add_alpha_indices(&mut self, alpha_indices: Vec<u8>) {
-        let mut node_idx = ROOT_NODE_IDX;
+        if self.are_alpha_indices_word(alpha_indices.clone()) {
+            return;
+        }
+        let mut builder = self.to_builder();
+        builder.add_alpha_indices(&alpha_indices);
+        *self = builder.build();
+    }
 
-        for c in alpha_indices.iter() {
-            node_idx = if let Some(next_idx) =
-            self.entries[node_idx].get_child_idx(*c) {
-                //This node has a child node that has been created
-                next_idx
-            } else {
-                //Create a new child node
-                self.entries.push(DictionaryTrieNode::new());
-                let new_node_idx = self.entries.len() - 1;
-                self.entries[node_idx].set_child_idx(*c, new_node_idx);
-                new_node_idx
-            }
+    fn to_builder(&self) -> TrieBuilder {
+        //Records are in the order of the builder's nodes, so a node's index is its record's rank
+        let mut node_indices = vec![0; self.records.len()];
+        let mut offset = 0;
+        let mut idx = 0;
+        while offset < self.records.len() {
+            node_indices[offset] = idx;
+            offset += 1 + (self.records[offset] & (IS_WORD_BIT - 1)).count_ones() as usize;
+            idx += 1;
         }
 
-        self.entries[node_idx].is_word = true;
+        let mut nodes = Vec::with_capacity(self.node_count);
+        let mut offset = 0;
+        while offset < self.records.len() {
+            let mask = self.records[offset];
+            let mut node = DictionaryTrieNode::new();
+            if mask & IS_WORD_BIT != 0 {
+                node.set_is_word();
+            }
+            let mut child_pos = offset + 1;
+            for alpha_idx in (0..26).filter(|idx| mask & (1 << idx) != 0) {
+                node.set_child_idx(alpha_idx, node_indices[self.records[child_pos] as usize]);
+                child_pos += 1;
+            }
+            nodes.push(node);
+            offset = child_pos;
+        }
+        TrieBuilder { nodes, word_count: self.word_count }
     }
 
     pub fn root(&self) -> DictionaryTrieNodePtr {
-        DictionaryTrieNodePtr { dict_trie: self, node: &self.entries[ROOT_NODE_IDX] }
+        DictionaryTrieNodePtr { dict_trie: self, offset: 0 }
     }
 
     pub fn find_node_from_alpha_indices(&self, alpha_indices: Vec<u8>) -> Option<DictionaryTrieNodePtr> {
@@ -135,25 +303,267 @@ impl DictionaryTrie {
     pub fn are_alpha_indices_word(&self, word: Vec<u8>) -> bool {
         self.find_node_from_alpha_indices(word).map_or(false, |n| n.is_word())
     }
+
+    ///
+    /// Words using every letter of the rack, blanks ('*') standing for any letter.
+    /// Words are lowercase and in alphabetical order
+    pub fn anagrams(&self, rack: &LetterBag) -> Vec<Word> {
+        let mut words = BTreeSet::new();
+        collect_anagrams(self.root(), rack, &mut Vec::new(), true, &mut words);
+        words.into_iter().collect()
+    }
+
+    ///
+    /// Words using any of the letters of the rack
+    pub fn subanagrams(&self, rack: &LetterBag) -> Vec<Word> {
+        let mut words = BTreeSet::new();
+        collect_anagrams(self.root(), rack, &mut Vec::new(), false, &mut words);
+        words.into_iter().collect()
+    }
+
+    ///
+    /// For each letter that can be added to the rack to make anagrams, the letter and those anagrams
+    pub fn rack_plus_one(&self, rack: &LetterBag) -> Vec<(Letter, Vec<Word>)> {
+        (b'a'..=b'z')
+            .map(|l| (l, self.anagrams(&rack.incremented(l))))
+            .filter(|(_, words)| !words.is_empty())
+            .collect()
+    }
+
+    ///
+    /// Back hooks are the children of the word's node that end words. Front hooks take
+    /// a lookup per letter of the alphabet, cheap enough not to need a reversed index
+    pub fn hooks(&self, word: &Word) -> Hooks {
+        let alpha_indices = word_to_alpha_indices(word);
+        let back = match self.find_node_from_alpha_indices(alpha_indices.clone()) {
+            None => String::new(),
+            Some(node) => (0..26)
+                .filter(|&idx| node.get_child_idx(idx).map_or(false, |child| child.is_word()))
+                .map(|idx| char::from(b'a' + idx))
+                .collect()
+        };
+
+        let front = (0..26)
+            .filter(|&idx| {
+                let mut hooked = vec![idx];
+                hooked.extend(alpha_indices.iter());
+                self.are_alpha_indices_word(hooked)
+            })
+            .map(|idx| char::from(b'a' + idx))
+            .collect();
+
+        Hooks { front, back }
+    }
+
+    ///
+    /// Words matching pattern, in alphabetical order. Branches of the trie that can't
+    /// match the pattern or its length and letter constraints are not walked
+    pub fn pattern_matches(&self, pattern: &WordPattern) -> Vec<Word> {
+        let mut words = Vec::new();
+        collect_pattern_matches(self.root(), pattern, pattern.start_states(), &mut Vec::new(), &mut words);
+        words
+    }
+
+    ///
+    /// Every word in the trie, lowercase and in alphabetical order
+    pub fn words(&self) -> Vec<Word> {
+        let mut words = Vec::with_capacity(self.word_count);
+        collect_words(self.root(), &mut Vec::new(), &mut words);
+        words
+    }
+
+    ///
+    /// Words added and removed going from this trie to other, found by walking both tries
+    /// together so that shared branches are only visited once
+    pub fn diff(&self, other: &DictionaryTrie) -> LexiconDiff {
+        let mut diff = LexiconDiff { added: Vec::new(), removed: Vec::new() };
+        collect_diff(Some(self.root()), Some(other.root()), &mut Vec::new(), &mut diff);
+        diff
+    }
+}
+
+fn collect_diff(node: Option<DictionaryTrieNodePtr>,
+                other_node: Option<DictionaryTrieNodePtr>,
+                word_so_far: &mut Vec<u8>,
+                diff: &mut LexiconDiff) {
+    match (node, other_node) {
+        (None, None) => (),
+        (Some(node), None) => collect_words(node, word_so_far, &mut diff.removed),
+        (None, Some(other_node)) => collect_words(other_node, word_so_far, &mut diff.added),
+        (Some(node), Some(other_node)) => {
+            match (node.is_word(), other_node.is_word()) {
+                (true, false) => diff.removed.push(String::from_utf8(word_so_far.clone()).unwrap()),
+                (false, true) => diff.added.push(String::from_utf8(word_so_far.clone()).unwrap()),
+                _ => ()
+            }
+
+            for alpha_idx in 0..26 {
+                word_so_far.push(b'a' + alpha_idx);
+                collect_diff(node.get_child_idx(alpha_idx), other_node.get_child_idx(alpha_idx), word_so_far, diff);
+                word_so_far.pop();
+            }
+        }
+    }
+}
+
+fn collect_words(node: DictionaryTrieNodePtr, word_so_far: &mut Vec<u8>, words: &mut Vec<Word>) {
+    if node.is_word() {
+        words.push(String::from_utf8(word_so_far.clone()).unwrap());
+    }
+
+    for alpha_idx in 0..26 {
+        if let Some(child) = node.get_child_idx(alpha_idx) {
+            word_so_far.push(b'a' + alpha_idx);
+            collect_words(child, word_so_far, words);
+            word_so_far.pop();
+        }
+    }
+}
+
+fn collect_pattern_matches(node: DictionaryTrieNodePtr,
+                           pattern: &WordPattern,
+                           states: PatternStates,
+                           word_so_far: &mut Vec<u8>,
+                           words: &mut Vec<Word>) {
+    let missing_letters = count_missing_letters(&pattern.must_contain, word_so_far);
+    if !pattern.could_match_with(word_so_far.len(), missing_letters) {
+        return;
+    }
+
+    if node.is_word()
+        && pattern.is_match(states)
+        && pattern.accepts_length(word_so_far.len())
+        && missing_letters == 0 {
+        words.push(String::from_utf8(word_so_far.clone()).unwrap());
+    }
+
+    if !pattern.allows_longer_than(word_so_far.len()) {
+        return;
+    }
+
+    for alpha_idx in 0..26 {
+        if let Some(child) = node.get_child_idx(alpha_idx) {
+            let next_states = pattern.advance(states, alpha_idx);
+            if next_states != 0 {
+                word_so_far.push(b'a' + alpha_idx);
+                collect_pattern_matches(child, pattern, next_states, word_so_far, words);
+                word_so_far.pop();
+            }
+        }
+    }
+}
+
+fn collect_anagrams(node: DictionaryTrieNodePtr,
+                    letters_available: &LetterBag,
+                    word_so_far: &mut Vec<u8>,
+                    use_all_letters: bool,
+                    words: &mut BTreeSet<Word>) {
+    if node.is_word() && (!use_all_letters || letters_available.size() == 0) {
+        words.insert(String::from_utf8(word_so_far.clone()).unwrap());
+    }
+
+    for &bag_letter in letters_available.keys() {
+        let next_letters_available = letters_available.decremented(bag_letter);
+        let alpha_indices = if bag_letter == WILDCARD_LETTER {
+            0..26
+        } else {
+            let alpha_idx = letter_alpha_idx(bag_letter);
+            alpha_idx..alpha_idx + 1
+        };
+
+        for alpha_idx in alpha_indices {
+            if let Some(child) = node.get_child_idx(alpha_idx) {
+                word_so_far.push(b'a' + alpha_idx);
+                collect_anagrams(child, &next_letters_available, word_so_far, use_all_letters, words);
+                word_so_far.pop();
+            }
+        }
+    }
 }
 
 
+//A trie that words can be added to quickly, packed into a DictionaryTrie once complete
+struct TrieBuilder {
+    nodes: Vec<DictionaryTrieNode>,
+    word_count: usize,
+}
+
+impl TrieBuilder {
+    fn new() -> TrieBuilder {
+        TrieBuilder { nodes: vec![DictionaryTrieNode::new()], word_count: 0 }
+    }
+
+    fn add_alpha_indices(&mut self, alpha_indices: &[u8]) {
+        let mut node_idx = ROOT_NODE_IDX;
+
+        for c in alpha_indices.iter() {
+            node_idx = if let Some(next_idx) =
+            self.nodes[node_idx].get_child_idx(*c) {
+                //This node has a child node that has been created
+                next_idx
+            } else {
+                //Create a new child node
+                self.nodes.push(DictionaryTrieNode::new());
+                let new_node_idx = self.nodes.len() - 1;
+                self.nodes[node_idx].set_child_idx(*c, new_node_idx);
+                new_node_idx
+            }
+        }
+
+        if !self.nodes[node_idx].is_word() {
+            self.nodes[node_idx].set_is_word();
+            self.word_count += 1;
+        }
+    }
+
+    fn build(self) -> DictionaryTrie {
+        //Nodes are created after their parents, so packing them in order keeps children after parents
+        let mut offsets = Vec::with_capacity(self.nodes.len());
+        let mut offset = 0;
+        for node in self.nodes.iter() {
+            offsets.push(offset);
+            offset += 1 + (node.mask & (IS_WORD_BIT - 1)).count_ones();
+        }
+
+        let mut records = Vec::with_capacity(offset as usize);
+        for node in self.nodes.iter() {
+            let mask = node.mask;
+            records.push(mask);
+            for alpha_idx in (0..26).filter(|idx| mask & (1 << idx) != 0) {
+                records.push(offsets[node.child_nodes[alpha_idx] as usize]);
+            }
+        }
+        DictionaryTrie { records, node_count: self.nodes.len(), word_count: self.word_count }
+    }
+}
+
 struct DictionaryTrieNode {
-    is_word: bool,
-    child_nodes: [usize; 26],
+    //The first word of the node's packed record, kept up to date as children are added
+    mask: u32,
+    //u32 rather than usize halves the memory needed while building a trie
+    child_nodes: [u32; 26],
 }
 
 impl DictionaryTrieNode {
     pub fn new() -> DictionaryTrieNode {
-        DictionaryTrieNode { is_word: false, child_nodes: [ROOT_NODE_IDX; 26] }
+        DictionaryTrieNode { mask: 0, child_nodes: [ROOT_NODE_IDX as u32; 26] }
+    }
+
+    fn is_word(&self) -> bool {
+        self.mask & IS_WORD_BIT != 0
+    }
+
+    fn set_is_word(&mut self) {
+        self.mask |= IS_WORD_BIT;
     }
 
     pub fn set_child_idx(&mut self, alpha_idx: u8, child_idx: usize) {
-        self.child_nodes[alpha_idx as usize] = child_idx;
+        self.child_nodes[alpha_idx as usize] = child_idx as u32;
+        self.mask |= 1 << alpha_idx;
     }
 
     pub fn get_child_idx(&self, alpha_index: u8) -> Option<usize> {
-        match self.child_nodes[alpha_index as usize] {
+        match self.child_nodes[alpha_index as usize] as usize {
             ROOT_NODE_IDX => None,
             i => Some(i)
         }
@@ -179,6 +589,51 @@ mod tests {
         assert!(!trie.is_word_string(&String::from("dogcatz")));
     }
 
+    #[test]
+    fn trie_word_count() {
+        let trie = DictionaryTrie::from_word_list("dog dogcat\ncat dog");
+        assert_eq!(trie.word_count(), 3);
+        assert_eq!(trie.words(), vec!["cat", "dog", "dogcat"]);
+    }
+
+    #[test]
+    fn trie_binary_round_trip() {
+        let trie = DictionaryTrie::from_word_list("cat cats dog do zax");
+        let bytes = trie.to_bytes();
+        let reread = DictionaryTrie::from_bytes(&bytes).unwrap();
+        assert_eq!(reread.words(), trie.words());
+        assert_eq!(reread.word_count(), 5);
+        assert!(trie.diff(&reread).added.is_empty());
+
+        let mut corrupt = bytes.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 1;
+        assert!(DictionaryTrie::from_bytes(&corrupt).is_err());
+        assert!(DictionaryTrie::from_bytes(&bytes[..bytes.len() - 4]).is_err());
+        assert!(DictionaryTrie::from_bytes(b"cat cats dog").is_err());
+
+        //The root's first child pointing past the last record, with a matching checksum
+        let mut out_of_range = bytes.clone();
+        out_of_range[BINARY_HEADER_LEN + 4..BINARY_HEADER_LEN + 8].copy_from_slice(&1000u32.to_le_bytes());
+        let sum = checksum(&out_of_range[BINARY_HEADER_LEN..]);
+        out_of_range[20..28].copy_from_slice(&sum.to_le_bytes());
+        assert!(DictionaryTrie::from_bytes(&out_of_range).err().unwrap().contains("corrupt"));
+
+        let mut newer = bytes.clone();
+        newer[8] = 2;
+        assert!(DictionaryTrie::from_bytes(&newer).err().unwrap().contains("version"));
+    }
+
+    #[test]
+    fn trie_diff() {
+        let old = DictionaryTrie::from_word_list("cat cats dog do zax");
+        let new = DictionaryTrie::from_word_list("cat dog dogs do za qi");
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec!["dogs", "qi", "za"]);
+        assert_eq!(diff.removed, vec!["cats", "zax"]);
+        assert_eq!(new.diff(&new), LexiconDiff { added: Vec::new(), removed: Vec::new() });
+    }
+
     #[test]
     fn trie_traversal() {
         let mut trie = DictionaryTrie::new();
@@ -194,6 +649,46 @@ mod tests {
         assert!(ptr.is_word());
     }
 
+    #[test]
+    fn trie_anagrams() {
+        let trie = DictionaryTrie::from_word_list("act cat tac at ta a cats scat");
+
+        let rack = LetterBag::from_string("tca");
+        assert_eq!(trie.anagrams(&rack), vec!["act", "cat", "tac"]);
+        assert_eq!(trie.subanagrams(&rack), vec!["a", "act", "at", "cat", "ta", "tac"]);
+
+        let rack = LetterBag::from_string("t*");
+        assert_eq!(trie.anagrams(&rack), vec!["at", "ta"]);
+
+        let plus_one = trie.rack_plus_one(&LetterBag::from_string("cat"));
+        assert_eq!(plus_one.len(), 1);
+        assert_eq!(plus_one[0].0, b's');
+        assert_eq!(plus_one[0].1, vec!["cats", "scat"]);
+    }
+
+    #[test]
+    fn trie_pattern_matches() {
+        let trie = DictionaryTrie::from_word_list("cat cot coat scat cast act cats");
+        let matches = |spec| trie.pattern_matches(&WordPattern::parse(spec).unwrap());
+
+        assert_eq!(matches("c?t"), vec!["cat", "cot"]);
+        assert_eq!(matches("*at*"), vec!["cat", "cats", "coat", "scat"]);
+        assert_eq!(matches("*[^a]t"), vec!["act", "cast", "cot"]);
+        assert_eq!(matches("* len=4"), vec!["cast", "cats", "coat", "scat"]);
+        assert_eq!(matches("c* has=ts len=3-4"), vec!["cast", "cats"]);
+    }
+
+    #[test]
+    fn trie_hooks() {
+        let trie = DictionaryTrie::from_word_list("care scare cared carer cares ca");
+        assert_eq!(trie.hooks(&String::from("care")),
+                   Hooks { front: String::from("s"), back: String::from("drs") });
+        assert_eq!(trie.hooks(&String::from("scare")),
+                   Hooks { front: String::new(), back: String::new() });
+        assert_eq!(trie.hooks(&String::from("are")),
+                   Hooks { front: String::from("c"), back: String::new() });
+    }
+
     #[test]
     fn scrabble_trie() {
         let trie = DictionaryTrie::from_scrabble_ospd();
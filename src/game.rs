@@ -0,0 +1,509 @@
+use super::dictionary::DictionaryTrie;
+use super::grid::Coord;
+use super::probability::TileDistribution;
+use super::util::{Direction, Letter, Word};
+use super::{ScrabbleBoard, scrabble_letter_score, WILDCARD_LETTER};
+use serde::Serialize;
+use std::sync::Arc;
+
+pub const RACK_SIZE: usize = 7;
+//The game ends after this many turns in a row without a score
+const MAX_SCORELESS_TURNS: u32 = 6;
+
+///
+/// What happens when a play forming a phony is challenged
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChallengeRule {
+    //A phony is withdrawn, an unsuccessful challenger loses their turn
+    Double,
+    //A phony is withdrawn, an unsuccessful challenger loses penalty points (usually 5 or 10)
+    Single { penalty: u32 },
+    //Plays forming phonies are rejected outright and the player tries again
+    Void,
+}
+
+impl ChallengeRule {
+    ///
+    /// double, single (5 point penalty), single=N or void
+    pub fn parse(spec: &str) -> Result<ChallengeRule, String> {
+        let mut parts = spec.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("double"), None) => Ok(ChallengeRule::Double),
+            (Some("single"), None) => Ok(ChallengeRule::Single { penalty: 5 }),
+            (Some("single"), Some(penalty)) => penalty.parse()
+                .map(|penalty| ChallengeRule::Single { penalty })
+                .map_err(|_| format!("single needs a number of points, got {}", penalty)),
+            (Some("void"), None) => Ok(ChallengeRule::Void),
+            _ => Err(format!("Unknown challenge rule {}", spec))
+        }
+    }
+}
+
+///
+/// Small xorshift generator, so a game can be replayed from its seed
+#[derive(Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        //xorshift gets stuck at 0
+        SeededRng { state: seed ^ 0x9e37_79b9_7f4a_7c15 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    ///
+    /// A number in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[derive(Clone)]
+pub struct Player {
+    pub name: String,
+    //Lowercase letters, '*' for blanks
+    pub rack: Vec<Letter>,
+    pub score: i32,
+}
+
+impl Player {
+    pub fn rack_string(&self) -> String {
+        let mut rack = self.rack.clone();
+        rack.sort();
+        String::from_utf8(rack).unwrap()
+    }
+}
+
+///
+/// Everything that happens in a game, in order
+#[derive(Clone, Serialize, PartialEq, Debug)]
+pub enum GameEvent {
+    Played { player: usize, word: Word, start_coord: Coord, direction: Direction, score: u32 },
+    Exchanged { player: usize, tiles: usize },
+    Passed { player: usize },
+    //A challenged play formed phonies, its tiles went back to the player's rack
+    PhonyWithdrawn { player: usize, phonies: Vec<Word>, score: u32 },
+    //Under the void rule, a play forming phonies was not allowed
+    PhonyRejected { player: usize, phonies: Vec<Word> },
+    //A challenged play was valid, the challenger lost their turn or penalty points
+    ChallengeFailed { challenger: usize, lost_turn: bool, penalty: u32 },
+    //Points gained or lost for tiles left on racks at the end, by player
+    Ended { rack_adjustments: Vec<i32> },
+}
+
+///
+/// An event with the rack of the player it concerns, sorted, as it was before the event.
+/// The rack is empty for the end of the game, which concerns every player
+#[derive(Clone, Serialize, PartialEq, Debug)]
+pub struct LoggedEvent {
+    pub event: GameEvent,
+    pub rack: Word,
+}
+
+//What's needed to take back the last play if it's successfully challenged
+struct ChallengeablePlay {
+    player: usize,
+    phonies: Vec<Word>,
+    score: u32,
+    board: ScrabbleBoard,
+    rack: Vec<Letter>,
+    bag: Vec<Letter>,
+    scoreless_turns: u32,
+}
+
+pub struct Game {
+    dict: Arc<DictionaryTrie>,
+    board: ScrabbleBoard,
+    bag: Vec<Letter>,
+    players: Vec<Player>,
+    to_move: usize,
+    rule: ChallengeRule,
+    rng: SeededRng,
+    events: Vec<LoggedEvent>,
+    last_play: Option<ChallengeablePlay>,
+    scoreless_turns: u32,
+    ended: bool,
+}
+
+pub(crate) fn remove_tiles(rack: &mut Vec<Letter>, tiles: &[Letter]) -> Result<(), String> {
+    let mut remaining = rack.clone();
+    for &tile in tiles {
+        match remaining.iter().position(|&t| t == tile) {
+            Some(pos) => { remaining.swap_remove(pos); },
+            None => return Err(format!("The rack has no {} to play", char::from(tile)))
+        }
+    }
+    *rack = remaining;
+    Ok(())
+}
+
+pub(crate) fn rack_value(rack: &[Letter]) -> i32 {
+    rack.iter()
+        .filter(|&&l| l != WILDCARD_LETTER)
+        .map(|&l| scrabble_letter_score(l) as i32)
+        .sum()
+}
+
+impl Game {
+    ///
+    /// A new game with a full standard bag, each player drawing a rack in turn.
+    /// The same seed always gives the same draws
+    pub fn new(dict: Arc<DictionaryTrie>, player_names: &[&str], rule: ChallengeRule, seed: u64) -> Game {
+        let distribution = TileDistribution::standard();
+        let mut bag = Vec::new();
+        for l in b'a'..=b'z' {
+            bag.extend(std::iter::repeat(l).take(distribution.count(l) as usize));
+        }
+        bag.extend(std::iter::repeat(WILDCARD_LETTER).take(distribution.blanks() as usize));
+
+        let players = player_names.iter()
+            .map(|name| Player { name: String::from(*name), rack: Vec::new(), score: 0 })
+            .collect();
+        let mut game = Game {
+            dict,
+            board: ScrabbleBoard::empty_scrabble_board(),
+            bag,
+            players,
+            to_move: 0,
+            rule,
+            rng: SeededRng::new(seed),
+            events: Vec::new(),
+            last_play: None,
+            scoreless_turns: 0,
+            ended: false,
+        };
+        for player in 0..game.players.len() {
+            game.draw(player);
+        }
+        game
+    }
+
+    pub fn board(&self) -> &ScrabbleBoard {
+        &self.board
+    }
+
+    pub fn dict(&self) -> &DictionaryTrie {
+        &self.dict
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn to_move(&self) -> usize {
+        self.to_move
+    }
+
+    pub fn rule(&self) -> ChallengeRule {
+        self.rule
+    }
+
+    pub fn events(&self) -> &[LoggedEvent] {
+        &self.events
+    }
+
+    pub fn bag_len(&self) -> usize {
+        self.bag.len()
+    }
+
+    ///
+    /// Tiles the player to move can't see: the bag and the other players' racks
+    pub fn unseen_tiles(&self) -> Vec<Letter> {
+        let mut unseen = self.bag.clone();
+        for (i, player) in self.players.iter().enumerate() {
+            if i != self.to_move {
+                unseen.extend(player.rack.iter());
+            }
+        }
+        unseen.sort();
+        unseen
+    }
+
+    ///
+    /// Whether the last play can still be challenged, i.e. nobody has moved since
+    pub fn can_challenge(&self) -> bool {
+        self.last_play.is_some() && self.rule != ChallengeRule::Void
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.ended
+            || self.scoreless_turns >= MAX_SCORELESS_TURNS
+            || (self.bag.is_empty() && self.players.iter().any(|p| p.rack.is_empty()))
+    }
+
+    fn draw(&mut self, player: usize) {
+        while self.players[player].rack.len() < RACK_SIZE && !self.bag.is_empty() {
+            let idx = self.rng.below(self.bag.len());
+            let tile = self.bag.swap_remove(idx);
+            self.players[player].rack.push(tile);
+        }
+    }
+
+    fn advance_turn(&mut self) {
+        self.to_move = (self.to_move + 1) % self.players.len();
+    }
+
+    fn check_can_move(&self) -> Result<(), String> {
+        if self.is_over() {
+            Err(String::from("The game is over"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn log(&mut self, rack: Word, event: GameEvent) -> GameEvent {
+        self.events.push(LoggedEvent { event: event.clone(), rack });
+        event
+    }
+
+    ///
+    /// Plays word (see ScrabbleBoard::validate_play) for the player to move. Errors leave the game
+    /// as it was. Under the void rule a play forming phonies is rejected, otherwise it stands
+    /// unless the next player challenges it
+    pub fn play(&mut self, start_coord: Coord, dir: Direction, word: &str) -> Result<GameEvent, String> {
+        self.check_can_move()?;
+        let validation = self.board.validate_play(start_coord, dir, word, &self.dict)?;
+        let player = self.to_move;
+        let rack_before = self.players[player].rack_string();
+        let mut rack = self.players[player].rack.clone();
+        remove_tiles(&mut rack, &validation.tiles_used())?;
+
+        let phonies: Vec<Word> = validation.phonies().iter().map(|w| w.word.clone()).collect();
+        if self.rule == ChallengeRule::Void && !phonies.is_empty() {
+            return Ok(self.log(rack_before, GameEvent::PhonyRejected { player, phonies }));
+        }
+
+        self.last_play = Some(ChallengeablePlay {
+            player,
+            phonies,
+            score: validation.score,
+            board: self.board.clone(),
+            rack: self.players[player].rack.clone(),
+            bag: self.bag.clone(),
+            scoreless_turns: self.scoreless_turns,
+        });
+        self.board.place_play(&validation);
+        self.players[player].rack = rack;
+        self.players[player].score += validation.score as i32;
+        self.scoreless_turns = 0;
+        self.draw(player);
+        self.advance_turn();
+
+        Ok(self.log(rack_before, GameEvent::Played {
+            player,
+            word: validation.word,
+            start_coord: validation.start_coord,
+            direction: validation.direction,
+            score: validation.score,
+        }))
+    }
+
+    ///
+    /// The player to move challenges the last play
+    pub fn challenge(&mut self) -> Result<GameEvent, String> {
+        if self.rule == ChallengeRule::Void {
+            return Err(String::from("There are no challenges under the void rule"));
+        }
+        let last_play = self.last_play.take().ok_or("There is no play to challenge")?;
+        let challenger = self.to_move;
+        let challenger_rack = self.players[challenger].rack_string();
+
+        if !last_play.phonies.is_empty() {
+            self.board = last_play.board;
+            self.bag = last_play.bag;
+            let player = &mut self.players[last_play.player];
+            player.rack = last_play.rack;
+            player.score -= last_play.score as i32;
+            //Withdrawing the play makes it a scoreless turn
+            self.scoreless_turns = last_play.scoreless_turns + 1;
+            let rack = self.players[last_play.player].rack_string();
+            return Ok(self.log(rack, GameEvent::PhonyWithdrawn {
+                player: last_play.player,
+                phonies: last_play.phonies,
+                score: last_play.score,
+            }));
+        }
+
+        let event = match self.rule {
+            ChallengeRule::Single { penalty } => {
+                self.players[challenger].score -= penalty as i32;
+                GameEvent::ChallengeFailed { challenger, lost_turn: false, penalty }
+            },
+            _ => {
+                self.scoreless_turns += 1;
+                self.advance_turn();
+                GameEvent::ChallengeFailed { challenger, lost_turn: true, penalty: 0 }
+            }
+        };
+        Ok(self.log(challenger_rack, event))
+    }
+
+    pub fn pass(&mut self) -> Result<GameEvent, String> {
+        self.check_can_move()?;
+        let player = self.to_move;
+        let rack = self.players[player].rack_string();
+        self.last_play = None;
+        self.scoreless_turns += 1;
+        self.advance_turn();
+        Ok(self.log(rack, GameEvent::Passed { player }))
+    }
+
+    ///
+    /// Swaps tiles ('*' for blanks) from the rack of the player to move for new ones from the bag
+    pub fn exchange(&mut self, tiles: &str) -> Result<GameEvent, String> {
+        self.check_can_move()?;
+        if self.bag.len() < RACK_SIZE {
+            return Err(format!("Exchanges need at least {} tiles in the bag", RACK_SIZE));
+        }
+        let tiles: Vec<Letter> = tiles.bytes().map(|l| l.to_ascii_lowercase()).collect();
+        if tiles.is_empty() {
+            return Err(String::from("No tiles to exchange"));
+        }
+
+        let player = self.to_move;
+        let rack = self.players[player].rack_string();
+        remove_tiles(&mut self.players[player].rack, &tiles)?;
+        self.last_play = None;
+        self.draw(player);
+        self.bag.extend(tiles.iter());
+        self.scoreless_turns += 1;
+        self.advance_turn();
+        Ok(self.log(rack, GameEvent::Exchanged { player, tiles: tiles.len() }))
+    }
+
+    ///
+    /// Once the game is over, settles the tiles left on racks: their values are taken off
+    /// each player's score, and a player who went out gains the other racks' values
+    pub fn end(&mut self) -> Result<GameEvent, String> {
+        if !self.is_over() {
+            return Err(String::from("The game is not over"));
+        }
+        if self.ended {
+            return Err(String::from("The game has already ended"));
+        }
+
+        let went_out = self.players.iter().position(|p| p.rack.is_empty() && self.bag.is_empty());
+        let rack_values: Vec<i32> = self.players.iter().map(|p| rack_value(&p.rack)).collect();
+        let total: i32 = rack_values.iter().sum();
+        let rack_adjustments: Vec<i32> = rack_values.iter()
+            .enumerate()
+            .map(|(i, &value)| if Some(i) == went_out { total } else { -value })
+            .collect();
+        for (player, adjustment) in self.players.iter_mut().zip(rack_adjustments.iter()) {
+            player.score += adjustment;
+        }
+
+        self.last_play = None;
+        self.ended = true;
+        Ok(self.log(Word::new(), GameEvent::Ended { rack_adjustments }))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_rack(&mut self, player: usize, rack: &str) {
+        self.players[player].rack = rack.bytes().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_game(rule: ChallengeRule) -> Game {
+        let dict = Arc::new(DictionaryTrie::from_word_list("dog dogs cat cats"));
+        let mut game = Game::new(dict, &["ann", "bob"], rule, 7);
+        game.set_rack(0, "dogzqa*");
+        game.set_rack(1, "catsxyz");
+        game
+    }
+
+    #[test]
+    fn seeded_draws() {
+        let dict = Arc::new(DictionaryTrie::from_word_list("dog"));
+        let first = Game::new(dict.clone(), &["ann", "bob"], ChallengeRule::Double, 42);
+        let second = Game::new(dict, &["ann", "bob"], ChallengeRule::Double, 42);
+        assert_eq!(first.players()[1].rack, second.players()[1].rack);
+        assert_eq!(first.players()[0].rack.len(), RACK_SIZE);
+        assert_eq!(first.bag_len(), 100 - 2 * RACK_SIZE);
+    }
+
+    #[test]
+    fn double_challenge() {
+        let mut game = new_game(ChallengeRule::Double);
+        game.play(Coord::new(7, 7), Direction::Right, "dog").unwrap();
+        assert_eq!(game.players()[0].score, 10);
+
+        //A valid play: the challenger loses their turn
+        let event = game.challenge().unwrap();
+        assert_eq!(event, GameEvent::ChallengeFailed { challenger: 1, lost_turn: true, penalty: 0 });
+        assert_eq!(game.to_move(), 0);
+        assert!(game.challenge().is_err());
+
+        game.set_rack(0, "zqaaaaa");
+        game.play(Coord::new(8, 7), Direction::Right, "za").unwrap();
+        match game.challenge().unwrap() {
+            GameEvent::PhonyWithdrawn { player, phonies, .. } => {
+                assert_eq!(player, 0);
+                assert_eq!(phonies, vec!["za", "dz", "oa"]);
+            },
+            other => panic!("Expected the play to be withdrawn, got {:?}", other)
+        }
+        assert_eq!(game.players()[0].score, 10);
+        assert_eq!(game.players()[0].rack_string(), "aaaaaqz");
+        assert!(game.board().get_letter(Coord::new(8, 7)).is_none());
+        assert_eq!(game.to_move(), 1);
+    }
+
+    #[test]
+    fn single_challenge_and_void() {
+        let mut game = new_game(ChallengeRule::Single { penalty: 10 });
+        game.play(Coord::new(7, 7), Direction::Right, "dog").unwrap();
+        game.challenge().unwrap();
+        assert_eq!(game.players()[1].score, -10);
+        assert_eq!(game.to_move(), 1);
+
+        let mut game = new_game(ChallengeRule::Void);
+        let event = game.play(Coord::new(7, 7), Direction::Right, "zoa").unwrap();
+        assert_eq!(event, GameEvent::PhonyRejected { player: 0, phonies: vec![String::from("zoa")] });
+        assert_eq!(game.to_move(), 0);
+        assert!(game.board().is_empty());
+        assert!(game.challenge().is_err());
+
+        let bag_len = game.bag_len();
+        game.play(Coord::new(7, 7), Direction::Right, "dOg").unwrap();
+        assert_eq!(game.players()[0].rack.len(), RACK_SIZE);
+        assert_eq!(game.bag_len(), bag_len - 3);
+        assert_eq!(game.board().get_letter(Coord::new(7, 8)), Some(b'O'));
+        game.set_rack(1, "tttttta");
+        assert!(game.play(Coord::new(8, 8), Direction::Right, "ax").is_err());
+    }
+
+    #[test]
+    fn game_end() {
+        let mut game = new_game(ChallengeRule::Double);
+        for _ in 0..MAX_SCORELESS_TURNS {
+            assert!(game.end().is_err());
+            game.pass().unwrap();
+        }
+        assert!(game.is_over());
+        assert!(game.pass().is_err());
+        let event = game.end().unwrap();
+        //d, o, g, z, q, a and c, a, t, s, x, y, z
+        assert_eq!(event, GameEvent::Ended { rack_adjustments: vec![-26, -28] });
+        assert!(game.end().is_err());
+    }
+
+    #[test]
+    fn challenge_rules() {
+        assert_eq!(ChallengeRule::parse("single=10").unwrap(), ChallengeRule::Single { penalty: 10 });
+        assert_eq!(ChallengeRule::parse("single").unwrap(), ChallengeRule::Single { penalty: 5 });
+        assert!(ChallengeRule::parse("triple").is_err());
+    }
+}
@@ -0,0 +1,545 @@
+use super::dictionary::DictionaryTrie;
+use super::game::{rack_value, remove_tiles, Game, GameEvent, LoggedEvent};
+use super::grid::Coord;
+use super::util::{Direction, Letter, Word};
+use super::{PlayValidation, ScrabbleBoard, WILDCARD_LETTER};
+use std::fmt::{Display, Formatter, Error};
+use std::fs;
+
+const GCG_BLANK: u8 = b'?';
+//Stands for a letter already on the board in the word of a play
+const PLAYED_THROUGH: u8 = b'.';
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GcgPlayer {
+    //What moves are recorded under, without spaces
+    pub nickname: String,
+    pub full_name: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum GcgAction {
+    //word: lowercase for tiles, capitals for blanks, '.' for letters already on the board
+    Play { start_coord: Coord, direction: Direction, word: Word },
+    //The player's play just before was challenged off
+    PhonyWithdrawn,
+    Pass,
+    //tiles: lowercase, '*' for blanks, empty when only how many were exchanged is known
+    Exchange { tiles: Word, count: usize },
+    //Points won or lost over a challenge of a valid play
+    Challenge,
+    TimePenalty,
+    //Tiles left on a rack at the end, scored for the player who went out or against their owner
+    EndRack { tiles: Word },
+}
+
+///
+/// One line of a game record, e.g. >Joel: AEINRST 8D NASTIER +70 70
+#[derive(Clone, PartialEq, Debug)]
+pub struct GcgMove {
+    pub nickname: String,
+    //Lowercase, '*' for blanks, empty if not recorded
+    pub rack: Word,
+    pub action: GcgAction,
+    pub score: i32,
+    //The player's total after the move
+    pub cumulative: i32,
+    pub notes: Vec<String>,
+}
+
+///
+/// A game record in the GCG format used to record tournament games
+#[derive(Clone, PartialEq, Debug)]
+pub struct GcgGame {
+    pub players: Vec<GcgPlayer>,
+    //Other pragmas before the first move in order, e.g. (title, Club game)
+    pub pragmas: Vec<(String, String)>,
+    pub moves: Vec<GcgMove>,
+}
+
+///
+/// The position after a move of a replayed game
+pub struct ReplayedTurn {
+    pub player: usize,
+    pub board: ScrabbleBoard,
+    //Every player's score, in the order of GcgGame::players
+    pub scores: Vec<i32>,
+    //For plays, the play as checked against the board before it
+    pub validation: Option<PlayValidation>,
+}
+
+//GCG tiles are capitals with ? for blanks
+fn tiles_from_gcg(tiles: &str) -> Result<Word, String> {
+    tiles.bytes()
+        .map(|l| match l {
+            b'A'..=b'Z' => Ok(char::from(l.to_ascii_lowercase())),
+            GCG_BLANK => Ok(char::from(WILDCARD_LETTER)),
+            _ => Err(format!("{} is not a tile", tiles))
+        })
+        .collect()
+}
+
+fn tiles_to_gcg(tiles: &str) -> String {
+    tiles.bytes()
+        .map(|l| if l == WILDCARD_LETTER { char::from(GCG_BLANK) } else { char::from(l.to_ascii_uppercase()) })
+        .collect()
+}
+
+//GCG words have capitals for tiles, lowercase for blanks and . or (LETTERS) for letters played through
+fn word_from_gcg(word: &str) -> Result<Word, String> {
+    let mut letters = Vec::new();
+    let mut in_parens = false;
+    for l in word.bytes() {
+        match l {
+            b'(' if !in_parens => in_parens = true,
+            b')' if in_parens => in_parens = false,
+            _ if in_parens && l.is_ascii_alphabetic() => letters.push(PLAYED_THROUGH),
+            PLAYED_THROUGH => letters.push(l),
+            b'A'..=b'Z' => letters.push(l.to_ascii_lowercase()),
+            b'a'..=b'z' => letters.push(l.to_ascii_uppercase()),
+            _ => return Err(format!("{} is not a word", word))
+        }
+    }
+    if in_parens || letters.is_empty() {
+        return Err(format!("{} is not a word", word));
+    }
+    Ok(String::from_utf8(letters).unwrap())
+}
+
+pub(crate) fn word_to_gcg(word: &str) -> String {
+    word.bytes()
+        .map(|l| char::from(if l.is_ascii_lowercase() { l.to_ascii_uppercase() } else { l.to_ascii_lowercase() }))
+        .collect()
+}
+
+fn parse_action(action: &str) -> Result<GcgAction, String> {
+    match action {
+        "--" => Ok(GcgAction::PhonyWithdrawn),
+        "-" => Ok(GcgAction::Pass),
+        "(challenge)" => Ok(GcgAction::Challenge),
+        "(time)" => Ok(GcgAction::TimePenalty),
+        _ if action.starts_with('-') => {
+            let exchanged = &action[1..];
+            match exchanged.parse::<usize>() {
+                Ok(count) => Ok(GcgAction::Exchange { tiles: Word::new(), count }),
+                Err(_) => {
+                    let tiles = tiles_from_gcg(exchanged)?;
+                    Ok(GcgAction::Exchange { count: tiles.len(), tiles })
+                }
+            }
+        },
+        _ if action.starts_with('(') && action.ends_with(')') && action.len() > 2 => {
+            Ok(GcgAction::EndRack { tiles: tiles_from_gcg(&action[1..action.len() - 1])? })
+        },
+        _ => Err(format!("Unknown move {}", action))
+    }
+}
+
+fn parse_move(line: &str) -> Result<GcgMove, String> {
+    let colon = line.find(':').ok_or_else(|| format!("{} has no player", line))?;
+    let nickname = String::from(line[1..colon].trim());
+    let tokens: Vec<&str> = line[colon + 1..].split_ascii_whitespace().collect();
+    if tokens.len() < 3 {
+        return Err(format!("{} is not a move", line));
+    }
+
+    let (fields, totals) = tokens.split_at(tokens.len() - 2);
+    let (score, cumulative) = match (totals[0].parse::<i32>(), totals[1].parse::<i32>()) {
+        (Ok(score), Ok(cumulative)) => (score, cumulative),
+        _ => return Err(format!("{} does not end with a score and a total", line))
+    };
+    let play = |position: &str, word: &str| -> Result<GcgAction, String> {
+        let (start_coord, direction) = Coord::parse_notation(position)?;
+        Ok(GcgAction::Play { start_coord, direction, word: word_from_gcg(word)? })
+    };
+    let (rack, action) = match fields {
+        [rack, position, word] => (tiles_from_gcg(rack)?, play(position, word)?),
+        [position, word] if Coord::parse_notation(position).is_ok() => (Word::new(), play(position, word)?),
+        [rack, action] => (tiles_from_gcg(rack)?, parse_action(action)?),
+        [action] => (Word::new(), parse_action(action)?),
+        _ => return Err(format!("{} is not a move", line))
+    };
+    Ok(GcgMove { nickname, rack, action, score, cumulative, notes: Vec::new() })
+}
+
+impl Display for GcgAction {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            GcgAction::Play { start_coord, direction, word } => {
+                write!(f, "{} {}", start_coord.notation(*direction), word_to_gcg(word))
+            },
+            GcgAction::PhonyWithdrawn => write!(f, "--"),
+            GcgAction::Pass => write!(f, "-"),
+            GcgAction::Exchange { tiles, count } if tiles.is_empty() => write!(f, "-{}", count),
+            GcgAction::Exchange { tiles, .. } => write!(f, "-{}", tiles_to_gcg(tiles)),
+            GcgAction::Challenge => write!(f, "(challenge)"),
+            GcgAction::TimePenalty => write!(f, "(time)"),
+            GcgAction::EndRack { tiles } => write!(f, "({})", tiles_to_gcg(tiles)),
+        }
+    }
+}
+
+impl Display for GcgMove {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, ">{}: {} {} {:+} {}", self.nickname, tiles_to_gcg(&self.rack), self.action, self.score, self.cumulative)
+    }
+}
+
+//The word of a GCG play with the letters it goes through filled in from the board
+fn resolve_played_through(board: &ScrabbleBoard, start_coord: Coord, dir: Direction, word: &str) -> Result<Word, String> {
+    let mut coord = start_coord;
+    let mut letters = Vec::new();
+    for l in word.bytes() {
+        if l == PLAYED_THROUGH {
+            let existing = board.get_letter(coord)
+                .ok_or_else(|| format!("There is no letter at {},{} to play through", coord.row, coord.col))?;
+            letters.push(existing);
+        } else {
+            letters.push(l);
+        }
+        coord = coord.next(dir);
+    }
+    Ok(String::from_utf8(letters).unwrap())
+}
+
+//The word of a play as written in a record, letters already on the board as '.'
+fn mark_played_through(board: &ScrabbleBoard, start_coord: Coord, dir: Direction, word: &str) -> Word {
+    let mut coord = start_coord;
+    let mut letters = Vec::new();
+    for l in word.bytes() {
+        letters.push(if board.get_letter(coord).is_some() { PLAYED_THROUGH } else { l });
+        coord = coord.next(dir);
+    }
+    String::from_utf8(letters).unwrap()
+}
+
+fn check_rack(rack: &str, tiles: &[Letter]) -> Result<(), String> {
+    if rack.is_empty() {
+        return Ok(());
+    }
+    let mut rack: Vec<Letter> = rack.bytes().collect();
+    remove_tiles(&mut rack, tiles)
+}
+
+impl GcgGame {
+    pub fn from_gcg(gcg: &str) -> Result<GcgGame, String> {
+        let mut game = GcgGame { players: Vec::new(), pragmas: Vec::new(), moves: Vec::new() };
+        for line in gcg.lines().map(|l| l.trim_end()) {
+            if line.starts_with('#') {
+                let mut parts = line[1..].splitn(2, char::is_whitespace);
+                let pragma = parts.next().unwrap_or("");
+                let value = parts.next().unwrap_or("").trim();
+                match pragma {
+                    "character-encoding" => (),
+                    "player1" | "player2" | "player3" | "player4" => {
+                        let mut names = value.splitn(2, char::is_whitespace);
+                        let nickname = String::from(names.next().unwrap_or(""));
+                        if nickname.is_empty() {
+                            return Err(format!("{} has no nickname", line));
+                        }
+                        let full_name = String::from(names.next().unwrap_or(&nickname).trim());
+                        game.players.push(GcgPlayer { nickname, full_name });
+                    },
+                    "note" if !game.moves.is_empty() => {
+                        game.moves.last_mut().unwrap().notes.push(String::from(value));
+                    },
+                    _ => game.pragmas.push((String::from(pragma), String::from(value)))
+                }
+            } else if line.starts_with('>') {
+                game.moves.push(parse_move(line)?);
+            } else if !line.trim().is_empty() {
+                //Notes can go on over several lines
+                match game.moves.last_mut().and_then(|m| m.notes.last_mut()) {
+                    Some(note) => {
+                        note.push('\n');
+                        note.push_str(line.trim());
+                    },
+                    None => return Err(format!("{} is not a pragma or a move", line))
+                }
+            }
+        }
+
+        for m in game.moves.iter() {
+            if game.player_idx(&m.nickname).is_none() {
+                return Err(format!("{} is not one of the players", m.nickname));
+            }
+        }
+        Ok(game)
+    }
+
+    pub fn from_file(path: &str) -> Result<GcgGame, String> {
+        let gcg = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read game {}: {}", path, e))?;
+        Self::from_gcg(&gcg)
+    }
+
+    ///
+    /// The record of a game so far. Exchanges only record how many tiles were exchanged, and a
+    /// lost turn for an unsuccessful challenge is recorded as a pass
+    pub fn from_game(game: &Game) -> GcgGame {
+        let players: Vec<GcgPlayer> = game.players().iter()
+            .map(|p| GcgPlayer {
+                nickname: p.name.split_ascii_whitespace().collect::<Vec<&str>>().join("_"),
+                full_name: p.name.clone(),
+            })
+            .collect();
+
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        let mut board_before_play = board.clone();
+        let mut recorded: Vec<(usize, Word, GcgAction, i32)> = Vec::new();
+        for LoggedEvent { event, rack } in game.events() {
+            match event {
+                GameEvent::Played { player, word, start_coord, direction, score } => {
+                    board_before_play = board.clone();
+                    let marked = mark_played_through(&board, *start_coord, *direction, word);
+                    board.add_word(*start_coord, *direction, word);
+                    let action = GcgAction::Play { start_coord: *start_coord, direction: *direction, word: marked };
+                    recorded.push((*player, rack.clone(), action, *score as i32));
+                },
+                GameEvent::PhonyWithdrawn { player, score, .. } => {
+                    board = board_before_play.clone();
+                    recorded.push((*player, rack.clone(), GcgAction::PhonyWithdrawn, -(*score as i32)));
+                },
+                GameEvent::Passed { player } => recorded.push((*player, rack.clone(), GcgAction::Pass, 0)),
+                GameEvent::Exchanged { player, tiles } => {
+                    let action = GcgAction::Exchange { tiles: Word::new(), count: *tiles };
+                    recorded.push((*player, rack.clone(), action, 0));
+                },
+                GameEvent::ChallengeFailed { challenger, lost_turn: true, .. } => {
+                    recorded.push((*challenger, rack.clone(), GcgAction::Pass, 0));
+                },
+                GameEvent::ChallengeFailed { challenger, penalty, .. } => {
+                    recorded.push((*challenger, rack.clone(), GcgAction::Challenge, -(*penalty as i32)));
+                },
+                GameEvent::PhonyRejected { .. } => (),
+                GameEvent::Ended { rack_adjustments } => {
+                    for (player, &adjustment) in rack_adjustments.iter().enumerate() {
+                        let own_rack = game.players()[player].rack_string();
+                        if adjustment > 0 {
+                            let mut others: Vec<Letter> = game.players().iter()
+                                .flat_map(|p| p.rack.iter().cloned())
+                                .collect();
+                            others.sort();
+                            let tiles = String::from_utf8(others).unwrap();
+                            recorded.push((player, Word::new(), GcgAction::EndRack { tiles }, adjustment));
+                        } else if adjustment < 0 {
+                            recorded.push((player, own_rack.clone(), GcgAction::EndRack { tiles: own_rack }, adjustment));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut cumulative = vec![0; players.len()];
+        let moves = recorded.into_iter()
+            .map(|(player, rack, action, score)| {
+                cumulative[player] += score;
+                let nickname = players[player].nickname.clone();
+                GcgMove { nickname, rack, action, score, cumulative: cumulative[player], notes: Vec::new() }
+            })
+            .collect();
+        GcgGame { players, pragmas: Vec::new(), moves }
+    }
+
+    pub fn to_gcg(&self) -> String {
+        let mut gcg = String::from("#character-encoding UTF-8\n");
+        for (i, player) in self.players.iter().enumerate() {
+            gcg.push_str(&format!("#player{} {} {}\n", i + 1, player.nickname, player.full_name));
+        }
+        for (pragma, value) in self.pragmas.iter() {
+            gcg.push_str(&format!("#{} {}\n", pragma, value));
+        }
+        for m in self.moves.iter() {
+            gcg.push_str(&format!("{}\n", m));
+            for note in m.notes.iter() {
+                gcg.push_str(&format!("#note {}\n", note));
+            }
+        }
+        gcg
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_gcg()).map_err(|e| format!("Could not save game {}: {}", path, e))
+    }
+
+    pub fn player_idx(&self, nickname: &str) -> Option<usize> {
+        self.players.iter().position(|p| p.nickname == nickname)
+    }
+
+    ///
+    /// Replays the moves from an empty board, checking that each play fits on the board and uses
+    /// tiles from the recorded rack, and that every score and total is right. Phonies are allowed,
+    /// since they stand unless challenged. Records don't say how many points a challenge or time
+    /// penalty is worth, so those lines are only checked to cost their player points, or for a
+    /// challenge bonus to go to the player whose play was just challenged
+    pub fn replay(&self, dict: &DictionaryTrie) -> Result<Vec<ReplayedTurn>, String> {
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        let mut scores = vec![0; self.players.len()];
+        let mut turns: Vec<ReplayedTurn> = Vec::new();
+        //Only the move right after a play can withdraw it
+        let mut board_before_play = None;
+
+        for (i, m) in self.moves.iter().enumerate() {
+            let player = self.player_idx(&m.nickname)
+                .ok_or_else(|| format!("{} is not one of the players", m.nickname))?;
+            let fail = |reason: String| format!("Move {} ({}): {}", i + 1, m, reason);
+            let withdrawable = board_before_play.take();
+            let mut validation = None;
+
+            let score_is_right = match &m.action {
+                GcgAction::Play { start_coord, direction, word } => {
+                    let word = resolve_played_through(&board, *start_coord, *direction, word).map_err(fail)?;
+                    let played = board.validate_play(*start_coord, *direction, &word, dict).map_err(fail)?;
+                    check_rack(&m.rack, &played.tiles_used()).map_err(fail)?;
+                    board_before_play = Some((player, board.clone()));
+                    board.place_play(&played);
+                    let score_is_right = m.score == played.score as i32;
+                    validation = Some(played);
+                    score_is_right
+                },
+                GcgAction::PhonyWithdrawn => {
+                    let withdrawn_score = turns.last().and_then(|t| t.validation.as_ref()).map(|v| v.score as i32);
+                    match (withdrawable, withdrawn_score) {
+                        (Some((played_by, previous_board)), Some(withdrawn_score)) if played_by == player => {
+                            board = previous_board;
+                            m.score == -withdrawn_score
+                        },
+                        _ => return Err(fail(String::from("There is no play of theirs to withdraw")))
+                    }
+                },
+                GcgAction::Pass => m.score == 0,
+                GcgAction::Exchange { tiles, .. } => {
+                    check_rack(&m.rack, tiles.as_bytes()).map_err(fail)?;
+                    m.score == 0
+                },
+                GcgAction::Challenge => match withdrawable {
+                    //The challenger's penalty, or the challenged player's bonus under some rules
+                    Some((played_by, _)) => if played_by == player { m.score >= 0 } else { m.score <= 0 },
+                    None => return Err(fail(String::from("There is no play to challenge")))
+                },
+                GcgAction::TimePenalty => m.score <= 0,
+                GcgAction::EndRack { tiles } => {
+                    let value = rack_value(tiles.as_bytes());
+                    if m.rack.is_empty() {
+                        //Going out scores the other racks once or, under some rules, twice
+                        m.score == value || m.score == 2 * value
+                    } else {
+                        m.score == -value
+                    }
+                }
+            };
+
+            if !score_is_right {
+                return Err(fail(format!("The score {} is wrong", m.score)));
+            }
+            scores[player] += m.score;
+            if scores[player] != m.cumulative {
+                return Err(fail(format!("The total should be {}", scores[player])));
+            }
+            turns.push(ReplayedTurn { player, board: board.clone(), scores: scores.clone(), validation });
+        }
+        Ok(turns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::game::ChallengeRule;
+    use std::sync::Arc;
+
+    const CLUB_GAME: &str = "\
+#character-encoding UTF-8
+#player1 ann Ann Smith
+#player2 bob Bob Jones
+#title Club game
+>ann: ADGOQZ? 8G DOG +10 10
+#note A quiet opening
+>bob: ACSTXYZ 8G ...S +6 6
+>ann: AEEQZ?E 9F ZA +16 26
+>ann: AEEQZ?E -- -16 10
+>bob: ACTXYZ? H7 C(O)t +4 10
+>ann: AEEEQZ? (challenge) -5 5
+>ann: AEEEQZ? -Q +0 5
+>bob: AEXYZEE - +0 10
+>bob: AEEXYZ (time) -10 0
+>ann: AEEEQZ? (AEEEQZ?) -24 -19
+>bob: AEEXYZ (AEEXYZ) -25 -25
+";
+
+    #[test]
+    fn gcg_round_trip() {
+        let game = GcgGame::from_gcg(CLUB_GAME).unwrap();
+        assert_eq!(game.players[1], GcgPlayer { nickname: String::from("bob"), full_name: String::from("Bob Jones") });
+        assert_eq!(game.pragmas, vec![(String::from("title"), String::from("Club game"))]);
+        assert_eq!(game.moves.len(), 11);
+        assert_eq!(game.moves[0].rack, "adgoqz*");
+        assert_eq!(game.moves[0].notes, vec!["A quiet opening"]);
+        assert_eq!(game.moves[4].action,
+                   GcgAction::Play { start_coord: Coord::new(6, 7), direction: Direction::Down, word: String::from("c.T") });
+        assert_eq!(game.moves[6].action, GcgAction::Exchange { tiles: String::from("q"), count: 1 });
+        assert_eq!(game.moves[9].action, GcgAction::EndRack { tiles: String::from("aeeeqz*") });
+        assert_eq!(game.moves[9].cumulative, -19);
+
+        assert_eq!(GcgGame::from_gcg(&game.to_gcg()).unwrap(), game);
+        assert!(game.to_gcg().contains(">bob: ACTXYZ? H7 C.t +4 10\n"));
+
+        assert!(GcgGame::from_gcg(">eve: ABC 8H CAB +14 14").is_err());
+        assert!(GcgGame::from_gcg("#player1 ann\n>ann: ABC 8Z9 CAB +14 14").is_err());
+        assert!(GcgGame::from_gcg("#player1 ann\n>ann: ABC 8H CAB fourteen 14").is_err());
+    }
+
+    #[test]
+    fn gcg_replay() {
+        let dict = DictionaryTrie::from_word_list("dog dogs cot za");
+        let game = GcgGame::from_gcg(CLUB_GAME).unwrap();
+        let turns = game.replay(&dict).unwrap();
+        assert_eq!(turns.len(), game.moves.len());
+        assert_eq!(turns[2].board.get_letter(Coord::new(8, 5)), Some(b'z'));
+        assert_eq!(turns[2].validation.as_ref().unwrap().phonies().len(), 1);
+        assert_eq!(turns[3].board.get_letter(Coord::new(8, 5)), None);
+        assert_eq!(turns[4].board.get_letter(Coord::new(8, 7)), Some(b'T'));
+        assert_eq!(turns.last().unwrap().scores, vec![-19, -25]);
+
+        let wrong_score = CLUB_GAME.replace("+6 6", "+7 7");
+        assert!(GcgGame::from_gcg(&wrong_score).unwrap().replay(&dict).err().unwrap().starts_with("Move 2"));
+        let wrong_total = CLUB_GAME.replace("+10 10\n", "+10 11\n");
+        assert!(GcgGame::from_gcg(&wrong_total).unwrap().replay(&dict).is_err());
+        let wrong_rack = CLUB_GAME.replace("ACSTXYZ", "ACTXYZE");
+        assert!(GcgGame::from_gcg(&wrong_rack).unwrap().replay(&dict).is_err());
+        let challenger_gains = CLUB_GAME.replace("(challenge) -5 5", "(challenge) +5 15");
+        assert!(GcgGame::from_gcg(&challenger_gains).unwrap().replay(&dict).err().unwrap().starts_with("Move 6"));
+        let nothing_challenged = CLUB_GAME.replace("-Q +0 5", "(challenge) +0 5");
+        assert!(GcgGame::from_gcg(&nothing_challenged).unwrap().replay(&dict).err().unwrap().starts_with("Move 7"));
+        let time_bonus = CLUB_GAME.replace("(time) -10 0", "(time) +10 20");
+        assert!(GcgGame::from_gcg(&time_bonus).unwrap().replay(&dict).err().unwrap().starts_with("Move 9"));
+    }
+
+    #[test]
+    fn game_export() {
+        let dict = Arc::new(DictionaryTrie::from_word_list("dog dogs cat cats za"));
+        let mut game = Game::new(dict.clone(), &["Ann Smith", "bob"], ChallengeRule::Double, 7);
+        game.set_rack(0, "dogzqa*");
+        game.set_rack(1, "catsxyz");
+        game.play(Coord::new(7, 7), Direction::Right, "dog").unwrap();
+        game.challenge().unwrap();
+        game.set_rack(0, "zqaaaaa");
+        game.play(Coord::new(8, 7), Direction::Right, "za").unwrap();
+        game.challenge().unwrap();
+        game.exchange("xyz").unwrap();
+        while !game.is_over() {
+            game.pass().unwrap();
+        }
+        game.end().unwrap();
+
+        let record = GcgGame::from_game(&game);
+        assert_eq!(record.players[0].nickname, "Ann_Smith");
+        assert_eq!(record.moves[0].to_string(), ">Ann_Smith: ?ADGOQZ 8H DOG +10 10");
+        assert_eq!(record.moves[1].action, GcgAction::Pass);
+        assert_eq!(record.moves[3].action, GcgAction::PhonyWithdrawn);
+        assert_eq!(record.moves[4].action, GcgAction::Exchange { tiles: Word::new(), count: 3 });
+        let scores: Vec<i32> = game.players().iter().map(|p| p.score).collect();
+        assert_eq!(record.replay(&dict).unwrap().last().unwrap().scores, scores);
+        assert_eq!(GcgGame::from_gcg(&record.to_gcg()).unwrap(), record);
+    }
+}
@@ -1,7 +1,7 @@
 use super::util::Direction;
 use serde::Serialize;
 
-#[derive(Clone, Copy, Serialize, PartialEq)]
+#[derive(Clone, Copy, Serialize, PartialEq, Debug)]
 pub struct Coord {
     pub row: i32,
     pub col: i32,
@@ -25,6 +25,41 @@ impl Coord {
             Direction::Down => Coord { row: self.row - 1, col: self.col },
         }
     }
+
+    ///
+    /// Parses standard notation, where 8H is a word across starting at row 8 column H and
+    /// H8 one down from the same square. Rows count from 1 and columns from A
+    pub fn parse_notation(notation: &str) -> Result<(Coord, Direction), String> {
+        let invalid = || format!("{} is not a position like 8H or H8", notation);
+        let digits_first = notation.starts_with(|c: char| c.is_ascii_digit());
+        let split = notation.find(|c: char| c.is_ascii_digit() != digits_first).ok_or_else(invalid)?;
+        let (first, second) = notation.split_at(split);
+        let (row, col, direction) = if digits_first {
+            (first, second, Direction::Right)
+        } else {
+            (second, first, Direction::Down)
+        };
+
+        let row = row.parse::<i32>().ok().filter(|&r| r >= 1).ok_or_else(invalid)?;
+        match col.as_bytes() {
+            [c] if c.is_ascii_alphabetic() => {
+                Ok((Coord::new(row - 1, (c.to_ascii_uppercase() - b'A') as i32), direction))
+            },
+            _ => Err(invalid())
+        }
+    }
+
+    ///
+    /// Standard notation for a word starting here going in direction d, see parse_notation.
+    /// Columns outside A to Z are written as ?
+    pub fn notation(&self, d: Direction) -> String {
+        let row = self.row + 1;
+        let col = if (0..26).contains(&self.col) { char::from(b'A' + self.col as u8) } else { '?' };
+        match d {
+            Direction::Right => format!("{}{}", row, col),
+            Direction::Down => format!("{}{}", col, row),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -77,6 +112,9 @@ impl<T> Grid<T> {
     }
 
     pub fn get(&self, coord: Coord) -> Option<T> where T: Clone {
+        if !self.is_coord_in_bounds(coord) {
+            return None;
+        }
         let offset = self.offset(coord);
         self.storage.get(offset).cloned()
     }
@@ -98,4 +136,24 @@ impl<T> Grid<T> {
     pub fn ncols(&self) -> usize {
         self.ncols
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coord_notation() {
+        assert_eq!(Coord::parse_notation("8H").unwrap(), (Coord::new(7, 7), Direction::Right));
+        assert_eq!(Coord::parse_notation("h8").unwrap(), (Coord::new(7, 7), Direction::Down));
+        assert_eq!(Coord::parse_notation("15A").unwrap(), (Coord::new(14, 0), Direction::Right));
+        assert_eq!(Coord::new(14, 0).notation(Direction::Right), "15A");
+        assert_eq!(Coord::new(0, 14).notation(Direction::Down), "O1");
+        assert_eq!(Coord::new(7, 300).notation(Direction::Right), "8?");
+        assert_eq!(Coord::new(7, -1).notation(Direction::Down), "?8");
+
+        for invalid in &["", "8", "H", "0H", "8HH", "H8H", "8,7"] {
+            assert!(Coord::parse_notation(invalid).is_err(), "{} should not parse", invalid);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use super::util::Word;
+use std::collections::HashMap;
+use std::fs;
+
+fn leave_key(leave: &str) -> Word {
+    let mut letters: Vec<u8> = leave.bytes().collect();
+    letters.sort();
+    String::from_utf8(letters).unwrap()
+}
+
+///
+/// Values of the tiles a player keeps after a move, in points.
+/// Leaves missing from the table are valued as the sum of their single tiles.
+#[derive(Clone)]
+pub struct LeaveTable {
+    values: HashMap<Word, f32>,
+}
+
+impl LeaveTable {
+    pub fn new() -> LeaveTable {
+        LeaveTable { values: HashMap::new() }
+    }
+
+    ///
+    /// spec: one leave per line as <letters> <value>, with '*' for blanks e.g.
+    /// *s  31.5
+    pub fn from_spec(spec: &str) -> Result<LeaveTable, String> {
+        let mut table = LeaveTable::new();
+        for line in spec.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_ascii_whitespace();
+            match (parts.next(), parts.next().map(|v| v.parse::<f32>())) {
+                (Some(letters), Some(Ok(value))) => table.set(letters, value),
+                _ => return Err(format!("{} is not in <letters> <value> format", line))
+            }
+        }
+        Ok(table)
+    }
+
+    pub fn from_file(path: &str) -> Result<LeaveTable, String> {
+        let spec = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read leave table {}: {}", path, e))?;
+        Self::from_spec(&spec)
+    }
+
+    /// Rough single tile values, a starting point until a table is learned for a lexicon
+    pub fn single_tiles() -> LeaveTable {
+        Self::from_spec(include_str!("../resources/scrabble_single_tile_leaves.txt")).unwrap()
+    }
+
+    pub fn set(&mut self, leave: &str, value: f32) {
+        self.values.insert(leave_key(leave), value);
+    }
+
+    pub fn value(&self, leave: &str) -> f32 {
+        let key = leave_key(leave);
+        match self.values.get(&key) {
+            Some(&value) => value,
+            None => key.bytes()
+                .map(|l| self.values.get(&(char::from(l).to_string())).cloned().unwrap_or(0.0))
+                .sum()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    ///
+    /// Writes the table in the format read by from_spec, shorter leaves first and leaves of the
+    /// same length in alphabetical order
+    pub fn to_spec(&self) -> String {
+        let mut entries: Vec<(&Word, &f32)> = self.values.iter().collect();
+        entries.sort_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| a.0.cmp(b.0)));
+        entries.iter()
+            .map(|(leave, value)| format!("{} {:.3}\n", leave, value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leave_values() {
+        let table = LeaveTable::from_spec("s 8\nq -7\n*s 35.5\n").unwrap();
+        assert_eq!(table.value("s*"), 35.5);
+        assert_eq!(table.value("qs"), 1.0);
+        assert_eq!(table.value("e"), 0.0);
+        assert_eq!(table.value(""), 0.0);
+    }
+
+    #[test]
+    fn leave_spec_round_trip() {
+        let table = LeaveTable::single_tiles();
+        assert_eq!(table.len(), 27);
+        let reread = LeaveTable::from_spec(&table.to_spec()).unwrap();
+        assert_eq!(reread.value("*"), table.value("*"));
+        assert!(LeaveTable::from_spec("abc").is_err());
+    }
+}
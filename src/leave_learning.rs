@@ -0,0 +1,240 @@
+use super::dictionary::DictionaryTrie;
+use super::game::{remove_tiles, ChallengeRule, Game, SeededRng};
+use super::leave::LeaveTable;
+use super::tournament::{Bot, BotMove};
+use super::util::{Letter, Word};
+use super::WILDCARD_LETTER;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+//a to z, then the blank
+const TILE_KINDS: usize = 27;
+//Keeps the regression solvable when some tile is never kept
+const RIDGE: f64 = 1.0;
+
+fn tile_idx(l: Letter) -> usize {
+    if l == WILDCARD_LETTER { TILE_KINDS - 1 } else { (l - b'a') as usize }
+}
+
+///
+/// Solves a x = b by Gaussian elimination with partial pivoting, a being n by n row major
+fn solve(mut a: Vec<f64>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).fold(col, |best, row| if a[row * n + col].abs() > a[best * n + col].abs() { row } else { best });
+        for k in 0..n {
+            a.swap(col * n + k, pivot * n + k);
+        }
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = a[row * n + col] / a[col * n + col];
+            for k in col..n {
+                a[row * n + k] -= factor * a[col * n + k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row * n + k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row * n + row];
+    }
+    x
+}
+
+///
+/// How far players pulled ahead of their opponent by their next turn after keeping each leave,
+/// gathered from self-play
+#[derive(Clone)]
+pub struct LeaveSamples {
+    //Sorted leave to the total of the spread gains that followed it and how many there were
+    leaves: HashMap<Word, (f64, usize)>,
+    total_score: f64,
+    count: usize,
+    //Sums over samples for regressing spread gains on the tiles kept: products of the
+    //counts of each pair of tiles, tile counts times gains, and tile counts
+    tile_products: Vec<f64>,
+    tile_scores: Vec<f64>,
+    tile_counts: Vec<f64>,
+}
+
+fn leave_of(bot_move: &BotMove, rack: &[Letter]) -> Result<Word, String> {
+    let mut leave = rack.to_vec();
+    match bot_move {
+        BotMove::Play(play) => return Ok(play.leave.clone()),
+        BotMove::Exchange(tiles) => remove_tiles(&mut leave, tiles.as_bytes())?,
+        BotMove::Pass => ()
+    }
+    leave.sort();
+    Ok(String::from_utf8(leave).unwrap())
+}
+
+impl LeaveSamples {
+    pub fn new() -> LeaveSamples {
+        LeaveSamples {
+            leaves: HashMap::new(),
+            total_score: 0.0,
+            count: 0,
+            tile_products: vec![0.0; TILE_KINDS * TILE_KINDS],
+            tile_scores: vec![0.0; TILE_KINDS],
+            tile_counts: vec![0.0; TILE_KINDS],
+        }
+    }
+
+    ///
+    /// Adds a leave, with the player's next turn score less the opponent's score in between
+    pub fn record(&mut self, leave: &str, spread_gain: i32) {
+        let mut key: Vec<u8> = leave.bytes().collect();
+        key.sort();
+        let score = spread_gain as f64;
+
+        let mut counts = [0.0; TILE_KINDS];
+        for &l in key.iter() {
+            counts[tile_idx(l)] += 1.0;
+        }
+        for i in 0..TILE_KINDS {
+            for j in 0..TILE_KINDS {
+                self.tile_products[i * TILE_KINDS + j] += counts[i] * counts[j];
+            }
+            self.tile_scores[i] += counts[i] * score;
+            self.tile_counts[i] += counts[i];
+        }
+
+        let entry = self.leaves.entry(String::from_utf8(key).unwrap()).or_insert((0.0, 0));
+        entry.0 += score;
+        entry.1 += 1;
+        self.total_score += score;
+        self.count += 1;
+    }
+
+    pub fn merge(&mut self, other: &LeaveSamples) {
+        for (leave, &(total, count)) in other.leaves.iter() {
+            let entry = self.leaves.entry(leave.clone()).or_insert((0.0, 0));
+            entry.0 += total;
+            entry.1 += count;
+        }
+        self.total_score += other.total_score;
+        self.count += other.count;
+        for (total, other) in self.tile_products.iter_mut().zip(other.tile_products.iter()) {
+            *total += other;
+        }
+        for i in 0..TILE_KINDS {
+            self.tile_scores[i] += other.tile_scores[i];
+            self.tile_counts[i] += other.tile_counts[i];
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    ///
+    /// Values leaves by how much more than average their players gained on the spread by their
+    /// next turn.
+    /// Single tiles get the values that best fit every sample as a sum of tile values (by
+    /// least squares), and each longer leave seen at least min_samples times gets its own
+    /// average, pulled towards the sum of its tiles the fewer times it was seen. Rarer leaves
+    /// are left out, so that they are valued as the sum of their tiles
+    pub fn to_table(&self, min_samples: usize) -> LeaveTable {
+        let mut table = LeaveTable::new();
+        if self.count == 0 {
+            return table;
+        }
+        let average = self.total_score / self.count as f64;
+
+        let mut products = self.tile_products.clone();
+        for i in 0..TILE_KINDS {
+            products[i * TILE_KINDS + i] += RIDGE;
+        }
+        let scores: Vec<f64> = (0..TILE_KINDS)
+            .map(|i| self.tile_scores[i] - average * self.tile_counts[i])
+            .collect();
+        let tile_values = solve(products, scores);
+        for (i, &value) in tile_values.iter().enumerate() {
+            let tile = if i == TILE_KINDS - 1 { WILDCARD_LETTER } else { b'a' + i as u8 };
+            table.set(&char::from(tile).to_string(), value as f32);
+        }
+
+        let prior_weight = min_samples.max(1) as f64;
+        for (leave, &(total, count)) in self.leaves.iter() {
+            if leave.len() > 1 && count >= min_samples.max(1) {
+                let additive: f64 = leave.bytes().map(|l| tile_values[tile_idx(l)]).sum();
+                let value = (total - average * count as f64 + additive * prior_weight) / (count as f64 + prior_weight);
+                table.set(leave, value as f32);
+            }
+        }
+        table
+    }
+}
+
+///
+/// Plays a game between two copies of bot, recording each leave kept while tiles were left to
+/// draw along with what its player scored on their next turn less what the opponent scored in
+/// between, so that leaves giving the opponent good openings count against themselves
+pub fn sample_game(dict: Arc<DictionaryTrie>, bot: &Bot, seed: u64, samples: &mut LeaveSamples) -> Result<(), String> {
+    let mut game = Game::new(dict, &["bot1", "bot2"], ChallengeRule::Double, seed);
+    let mut rng = SeededRng::new(!seed);
+    //By player, the leave kept on their last turn, if it was refilled from the bag, and their
+    //lead after that turn
+    let mut kept: Vec<Option<(Word, i32)>> = vec![None, None];
+
+    while !game.is_over() {
+        let player = game.to_move();
+        let bot_move = bot.choose(&game, &mut rng);
+        let leave = leave_of(&bot_move, &game.players()[player].rack)?;
+        bot_move.make(&mut game)?;
+        let spread = game.players()[player].score - game.players()[1 - player].score;
+        if let Some((previous, previous_spread)) = kept[player].take() {
+            samples.record(&previous, spread - previous_spread);
+        }
+        if game.bag_len() > 0 {
+            kept[player] = Some((leave, spread));
+        }
+    }
+    Ok(())
+}
+
+///
+/// Samples the games numbered first, first + step, ... below games, see sample_game
+pub fn sample_games(dict: Arc<DictionaryTrie>,
+                    bot: &Bot,
+                    seed: u64,
+                    games: usize,
+                    first: usize,
+                    step: usize) -> Result<LeaveSamples, String> {
+    let mut samples = LeaveSamples::new();
+    for game in (first..games).step_by(step.max(1)) {
+        sample_game(Arc::clone(&dict), bot, seed.wrapping_add(game as u64), &mut samples)?;
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leave_values_from_samples() {
+        let mut samples = LeaveSamples::new();
+        samples.record("s", 40);
+        samples.record("s", 30);
+        samples.record("qv", 10);
+        samples.record("vq", -10);
+        samples.record("", 20);
+        let table = samples.to_table(2);
+        //Every single tile, and qv
+        assert_eq!(table.len(), 28);
+        assert!(table.value("s") > 0.0);
+        assert!(table.value("qv") < 0.0);
+        assert_eq!(table.value("z"), 0.0);
+        assert!(table.value("qv") != table.value("q") + table.value("v"));
+        assert_eq!(samples.to_table(3).len(), 27);
+
+        let dict = Arc::new(DictionaryTrie::from_scrabble_ospd());
+        let mut learned = sample_games(dict, &Bot::Equity { leaves: LeaveTable::single_tiles() }, 3, 1, 0, 1).unwrap();
+        assert!(learned.len() > 0);
+        let count = learned.len();
+        learned.merge(&samples);
+        assert_eq!(learned.len(), count + 5);
+    }
+}
@@ -34,6 +34,15 @@ impl VecLetterBag {
         bag
     }
 
+    pub fn incremented(&self, key: Letter) -> VecLetterBag {
+        let mut bag = self.clone();
+        match bag.find_entry_mut(key) {
+            Some((_, value)) => *value += 1,
+            None => bag.bag.push((key, 1))
+        }
+        bag
+    }
+
     pub fn from_string(s:&str) -> VecLetterBag {
         let mut counts = HashMap::new();
         for b in s.bytes() {
@@ -46,6 +55,20 @@ impl VecLetterBag {
 
     }
 
+    ///
+    /// Reads a rack typed by a user: letters in either case, with '*' or '?' for blanks
+    pub fn from_rack(rack:&str) -> Result<VecLetterBag, String> {
+        let mut letters = String::new();
+        for c in rack.chars() {
+            match c {
+                'a'..='z' | 'A'..='Z' => letters.push(c.to_ascii_lowercase()),
+                '*' | '?' => letters.push('*'),
+                _ => return Err(format!("{} is not a letter or blank", c))
+            }
+        }
+        Ok(VecLetterBag::from_string(&letters))
+    }
+
     pub fn keys(&self) -> impl Iterator<Item=&u8> {
         self.bag.iter()
             .filter(|t| t.1>0)
@@ -55,6 +78,17 @@ impl VecLetterBag {
     pub fn size(&self) -> u32 {
         return self.bag.iter().map(|(letter,count)| count).sum();
     }
+
+    pub fn to_sorted_string(&self) -> String {
+        let mut letters = Vec::new();
+        for &(letter, count) in self.bag.iter() {
+            for _ in 0..count {
+                letters.push(letter);
+            }
+        }
+        letters.sort();
+        String::from_utf8(letters).unwrap()
+    }
 }
 
 #[derive(Clone)]
@@ -116,4 +150,19 @@ mod tests {
         assert_eq!(lb.size(),4);
     }
 
+    #[test]
+    fn rack_tests() {
+        let lb = LetterBag::from_rack("Qi?").unwrap();
+        assert_eq!(lb.to_sorted_string(), "*iq");
+        assert_eq!(lb.incremented(b'i').incremented(b'z').to_sorted_string(), "*iiqz");
+        assert!(LetterBag::from_rack("q1").is_err());
+    }
+
+    #[test]
+    fn sorted_string_tests() {
+        let lb = LetterBag::from_string("sab*a");
+        assert_eq!(lb.to_sorted_string(), "*aabs");
+        assert_eq!(lb.decremented(b'a').decremented(b'*').to_sorted_string(), "abs");
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,186 @@
+use super::definitions::Definitions;
+use super::dictionary::DictionaryTrie;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+const BUILTIN_PREFIX: &str = "builtin:";
+//Sources with this extension are dictionaries saved by DictionaryTrie::write_binary_file
+const BINARY_EXTENSION: &str = ".trie";
+const LEXICON_CONFIG_ENV_VAR: &str = "SCRABBLE_LEXICONS";
+
+pub struct Lexicon {
+    pub name: String,
+    //Shared so that searches can run on other threads, e.g. when streaming solutions
+    pub dict: Arc<DictionaryTrie>,
+    //Empty unless a definitions file is configured for the lexicon
+    pub definitions: Definitions,
+}
+
+///
+/// A named collection of dictionaries. The first lexicon listed is the default one,
+/// used whenever a caller doesn't ask for a lexicon by name.
+pub struct LexiconSet {
+    lexicons: Vec<Lexicon>,
+}
+
+///
+/// Loads builtin:ospd, builtin:2019, a binary .trie file or a word list, paths being relative
+/// to base_dir
+pub fn load_lexicon_source(source: &str, base_dir: &Path) -> Result<DictionaryTrie, String> {
+    if source.starts_with(BUILTIN_PREFIX) {
+        match &source[BUILTIN_PREFIX.len()..] {
+            "ospd" => Ok(DictionaryTrie::from_scrabble_ospd()),
+            "2019" => Ok(DictionaryTrie::from_scrabble_2019()),
+            other => Err(format!("Unknown builtin lexicon {}", other))
+        }
+    } else {
+        let path = base_dir.join(source);
+        let path = path.to_string_lossy();
+        if source.ends_with(BINARY_EXTENSION) {
+            DictionaryTrie::from_binary_file(&path)
+        } else {
+            DictionaryTrie::from_file(&path)
+        }
+    }
+}
+
+impl LexiconSet {
+    pub fn new() -> LexiconSet {
+        LexiconSet { lexicons: Vec::new() }
+    }
+
+    pub fn builtin() -> LexiconSet {
+        let mut lexicons = LexiconSet::new();
+        lexicons.add("ospd", DictionaryTrie::from_scrabble_ospd());
+        lexicons.add("nwl2019", DictionaryTrie::from_scrabble_2019());
+        lexicons
+    }
+
+    ///
+    /// config: one lexicon per line as <name> <source> [<definitions>], where source is either
+    /// builtin:ospd, builtin:2019, the path of a word list file or of a binary dictionary
+    /// file ending in .trie, and definitions the path
+    /// of a file of <word><TAB><definition> lines.
+    /// Relative paths are resolved against base_dir. Lines starting with '#' are ignored.
+    pub fn from_config(config: &str, base_dir: &Path) -> Result<LexiconSet, String> {
+        let mut lexicons = LexiconSet::new();
+        for line in config.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_ascii_whitespace();
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(source), definitions, None) => {
+                    if lexicons.get(Some(name)).is_ok() {
+                        return Err(format!("Lexicon {} is configured twice", name));
+                    }
+                    let dict = load_lexicon_source(source, base_dir)?;
+                    lexicons.add(name, dict);
+                    if let Some(definitions) = definitions {
+                        let path = base_dir.join(definitions);
+                        lexicons.set_definitions(name, Definitions::from_file(&path.to_string_lossy())?)?;
+                    }
+                }
+                _ => {
+                    return Err(format!("{} is not in <name> <source> [<definitions>] format", line));
+                }
+            }
+        }
+
+        if lexicons.lexicons.is_empty() {
+            Err(String::from("No lexicons configured"))
+        } else {
+            Ok(lexicons)
+        }
+    }
+
+    pub fn from_config_file(path: &str) -> Result<LexiconSet, String> {
+        let config = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read lexicon config {}: {}", path, e))?;
+        let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+        Self::from_config(&config, base_dir)
+    }
+
+    ///
+    /// Loads the lexicons listed in the file named by $SCRABBLE_LEXICONS,
+    /// or resources/lexicons.txt when it isn't set
+    pub fn from_configured_file() -> Result<LexiconSet, String> {
+        let config_path = env::var(LEXICON_CONFIG_ENV_VAR)
+            .unwrap_or_else(|_| String::from(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/lexicons.txt")));
+        Self::from_config_file(&config_path)
+    }
+
+    pub fn add(&mut self, name: &str, dict: DictionaryTrie) {
+        self.lexicons.push(Lexicon {
+            name: String::from(name),
+            dict: Arc::new(dict),
+            definitions: Definitions::new(),
+        });
+    }
+
+    pub fn set_definitions(&mut self, name: &str, definitions: Definitions) -> Result<(), String> {
+        let lexicon = self.lexicons.iter_mut()
+            .find(|l| l.name == name)
+            .ok_or_else(|| format!("Unknown lexicon {}", name))?;
+        lexicon.definitions = definitions;
+        Ok(())
+    }
+
+    /// Looks up a lexicon by name, falling back to the default lexicon when name is None
+    pub fn get(&self, name: Option<&str>) -> Result<&Lexicon, String> {
+        match name {
+            None => self.lexicons.first()
+                .ok_or_else(|| String::from("No lexicons loaded")),
+            Some(name) => self.lexicons.iter()
+                .find(|l| l.name == name)
+                .ok_or_else(|| format!("Unknown lexicon {}", name))
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Lexicon> {
+        self.lexicons.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexicon_lookup() {
+        let mut lexicons = LexiconSet::new();
+        lexicons.add("small", DictionaryTrie::from_word_list("dog cat"));
+        lexicons.add("tiny", DictionaryTrie::from_word_list("dog"));
+
+        assert_eq!(lexicons.get(None).unwrap().name, "small");
+        assert_eq!(lexicons.get(Some("tiny")).unwrap().dict.word_count(), 1);
+        assert!(lexicons.get(Some("collins")).is_err());
+
+        lexicons.set_definitions("tiny", Definitions::from_spec("dog\tcanine").unwrap()).unwrap();
+        assert_eq!(lexicons.get(Some("tiny")).unwrap().definitions.get("dog"), Some("canine"));
+        assert_eq!(lexicons.get(Some("small")).unwrap().definitions.get("dog"), None);
+        assert!(lexicons.set_definitions("collins", Definitions::new()).is_err());
+    }
+
+    #[test]
+    fn lexicon_config_errors() {
+        let base_dir = Path::new(".");
+        assert!(LexiconSet::from_config("# nothing here\n", base_dir).is_err());
+        assert!(LexiconSet::from_config("ospd", base_dir).is_err());
+        assert!(LexiconSet::from_config("ospd builtin:collins", base_dir).is_err());
+        assert!(LexiconSet::from_config("mine no_such_file.txt", base_dir).is_err());
+        assert!(LexiconSet::from_config("mine no_such_file.trie", base_dir).is_err());
+        assert!(LexiconSet::from_config("ospd builtin:ospd no_such_file.txt", base_dir).is_err());
+        assert!(LexiconSet::from_config("ospd builtin:ospd a.txt b.txt", base_dir).is_err());
+
+        let temp_dir = env::temp_dir();
+        fs::write(temp_dir.join("scrabble_bad_words.txt"), "#WORDS\ndog can't\n").unwrap();
+        fs::write(temp_dir.join("scrabble_good_words.txt"), "dog\ncat\n").unwrap();
+        assert!(LexiconSet::from_config("mine scrabble_bad_words.txt", &temp_dir).is_err());
+        assert!(LexiconSet::from_config("mine scrabble_good_words.txt", &temp_dir).is_ok());
+    }
+}
@@ -1,7 +1,23 @@
+pub mod analysis;
+mod definitions;
 mod dictionary;
+pub mod game;
+pub mod gcg;
 mod grid;
+mod leave;
+pub mod leave_learning;
 mod letter_bag;
+mod lexicon;
+mod pattern;
+mod position;
+mod probability;
+pub mod quiz;
+mod render;
+mod solve_query;
+mod top_solutions;
+pub mod tournament;
 mod util;
+pub mod wordlists;
 
 use std::collections::{HashMap, VecDeque};
 use std::slice::from_ref;
@@ -12,7 +28,16 @@ use serde::Serialize;
 pub use util::Direction;
 use util::{Letter,Word};
 pub use letter_bag::LetterBag;
-pub use dictionary::DictionaryTrie;
+pub use definitions::Definitions;
+pub use dictionary::{DictionaryTrie, Hooks, LexiconDiff};
+pub use lexicon::{Lexicon, LexiconSet, load_lexicon_source};
+pub use top_solutions::{TopSolutions, SolutionRanking, BestBlankDesignations, compare_solutions};
+pub use leave::LeaveTable;
+pub use solve_query::SolveQuery;
+pub use pattern::WordPattern;
+pub use position::Position;
+pub use render::BoardRenderer;
+pub use probability::TileDistribution;
 use dictionary::DictionaryTrieNodePtr;
 pub use grid::Coord;
 use grid::Grid;
@@ -20,6 +45,8 @@ use std::fmt::{Display, Formatter, Error};
 
 const CAPITAL_A_TO_Z:&str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
 pub const WILDCARD_LETTER:Letter = b'*';
+//How to_text writes an empty square
+const EMPTY_SQUARE_TEXT: char = '.';
 
 lazy_static! {
     //Official Scrabble letter values
@@ -80,27 +107,108 @@ pub struct ScrabbleSolution {
     pub score: u32,
     pub direction: Direction,
     pub start_coord: Coord,
+    //Rack letters not used by the solution, in alphabetical order
+    pub leave: Word,
+    pub tiles_placed: u32,
+}
+
+impl ScrabbleSolution {
+    pub fn equity(&self, leaves: &LeaveTable) -> f32 {
+        self.score as f32 + leaves.value(&self.leave)
+    }
+}
+
+///
+/// Receives solutions as the search finds them
+pub trait SolutionSink {
+    fn accept(&mut self, solution: ScrabbleSolution);
+
+    ///
+    /// Whether the sink wants no more solutions, so that the search can stop early
+    fn is_done(&self) -> bool {
+        false
+    }
+}
+
+impl<F> SolutionSink for F where F: FnMut(ScrabbleSolution) {
+    fn accept(&mut self, solution: ScrabbleSolution) {
+        self(solution)
+    }
 }
 
 impl Display for ScrabbleSolution {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f,
-               "{}: {},{}({}): {} points",
+               "{}: {}: {} points",
                 self.word,
-                self.start_coord.row,
-                self.start_coord.col,
-                if let Direction::Right = self.direction {"R"} else {"D"},
-                self.score);
-        Ok(())
+                self.start_coord.notation(self.direction),
+                self.score)
+    }
+}
+
+///
+/// A word formed by a play, either its main word or a cross word, and what it scores
+#[derive(Clone, Serialize)]
+pub struct FormedWord {
+    pub word: Word,
+    pub start_coord: Coord,
+    pub direction: Direction,
+    pub score: u32,
+    pub is_valid: bool,
+}
+
+///
+/// A play checked against the board and a dictionary. The play fits on the board,
+/// but words it forms may be phonies.
+pub struct PlayValidation {
+    //The main word including letters already on the board, blanks as capitals
+    pub word: Word,
+    pub start_coord: Coord,
+    pub direction: Direction,
+    //Squares the play puts tiles on, in order along the word
+    pub placed: Vec<Coord>,
+    pub words: Vec<FormedWord>,
+    pub score: u32,
+}
+
+impl PlayValidation {
+    pub fn phonies(&self) -> Vec<&FormedWord> {
+        self.words.iter().filter(|w| !w.is_valid).collect()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.words.iter().all(|w| w.is_valid)
+    }
+
+    ///
+    /// The rack tiles the play uses, '*' for blanks
+    pub fn tiles_used(&self) -> Vec<Letter> {
+        let mut coord = self.start_coord;
+        let mut tiles = Vec::new();
+        for l in self.word.bytes() {
+            if self.placed.contains(&coord) {
+                tiles.push(if l.is_ascii_uppercase() { WILDCARD_LETTER } else { l });
+            }
+            coord = coord.next(self.direction);
+        }
+        tiles
     }
 }
 
+///
+/// One page of the best solutions, along with how many solutions the query accepted in total
+pub struct SolutionsPage {
+    pub total: usize,
+    pub solutions: Vec<ScrabbleSolution>,
+}
+
 struct ScrabbleSolutionBuilder<'a> {
     word_so_far: Word,
     trie_ptr: DictionaryTrieNodePtr<'a>,
     letters_available: LetterBag,
     anchored: bool,
     letters_placed: u32,
+    cross_words: u32,
     letter_score: u32,
     word_multiplier: u32,
     addon_score: u32,
@@ -120,6 +228,7 @@ impl<'a> ScrabbleSolutionBuilder<'a> {
             letters_available: letters_available.clone(),
             anchored: false,
             letters_placed: 0,
+            cross_words: 0,
             letter_score: 0,
             word_multiplier: 1,
             addon_score: 0,
@@ -143,13 +252,43 @@ impl<'a> ScrabbleSolutionBuilder<'a> {
             score: self.final_score(),
             direction: dir,
             start_coord,
+            leave: self.letters_available.to_sorted_string(),
+            tiles_placed: self.letters_placed,
         }
     }
 
+    fn report_if_valid(&self,
+                       board: &ScrabbleBoard,
+                       dir: Direction,
+                       end_coord: Coord,
+                       query: &SolveQuery,
+                       sink: &mut dyn SolutionSink) {
+        if !self.is_valid_solution() {
+            return;
+        }
+        let solution = self.build(dir, end_coord);
+        if !query.accepts(&solution) {
+            return;
+        }
+        //A single tile forming words both across and down is found once in each direction,
+        // the down one is only reported when the query turns the across one away
+        if self.has_across_twin(dir) {
+            let across = board.across_twin(&solution);
+            if query.allows_start(across.start_coord, Direction::Right) && query.accepts(&across) {
+                return;
+            }
+        }
+        sink.accept(solution);
+    }
+
     fn is_valid_solution(&self) -> bool {
         self.anchored && (self.letters_placed > 0) && self.trie_ptr.is_word()
     }
 
+    fn has_across_twin(&self, dir: Direction) -> bool {
+        dir == Direction::Down && self.letters_placed == 1 && self.cross_words == 1
+    }
+
     fn get_trie_child(&self, l: Letter) -> Option<DictionaryTrieNodePtr> {
         self.trie_ptr.get_child(l)
     }
@@ -185,11 +324,7 @@ pub fn print_top_solutions(solutions: &Vec<ScrabbleSolution>,
     sorted_solutions.sort_by(
         |a,b| b.score.cmp(&a.score));
     for (i,solution) in sorted_solutions.iter().enumerate() {
-        println!("{}: {},{}({}): {} points", solution.word,
-                 solution.start_coord.row,
-                 solution.start_coord.col,
-                 if let Direction::Right = solution.direction {"R"} else {"D"},
-                 solution.score);
+        println!("{}", solution);
         if let Some(limit) = limit {
             if i>limit {
                 break;
@@ -234,6 +369,114 @@ impl ScrabbleBoard {
         self.letters.set_unchecked(coord, Some(l))
     }
 
+    ///
+    /// Takes the tile off a square, returning it, or None if the square is empty or off the board
+    pub fn remove_letter(&mut self, coord: Coord) -> Option<Letter> {
+        let l = self.get_letter(coord)?;
+        self.letters.set_unchecked(coord, None);
+        Some(l)
+    }
+
+    ///
+    /// One line per row, . for empty squares, capitals for tiles and lowercase for blanks
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for i in 0..self.nrows() as i32 {
+            for j in 0..self.ncols() as i32 {
+                text.push(match self.get_letter(Coord::new(i, j)) {
+                    Some(l) if l.is_ascii_uppercase() => char::from(l.to_ascii_lowercase()),
+                    Some(l) => char::from(l.to_ascii_uppercase()),
+                    None => EMPTY_SQUARE_TEXT
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    ///
+    /// The board on one line like a chess FEN: rows from the top separated by /, each row giving
+    /// capitals for tiles, lowercase for blanks and the number of empty squares between them e.g.
+    /// 15/15/15/15/15/15/15/7DoG5/15/15/15/15/15/15/15
+    pub fn to_board_string(&self) -> String {
+        let mut rows = Vec::new();
+        for i in 0..self.nrows() as i32 {
+            let mut row = String::new();
+            let mut empty_run = 0;
+            for j in 0..self.ncols() as i32 {
+                match self.get_letter(Coord::new(i, j)) {
+                    Some(l) => {
+                        if empty_run > 0 {
+                            row.push_str(&empty_run.to_string());
+                            empty_run = 0;
+                        }
+                        row.push(char::from(if l.is_ascii_uppercase() { l.to_ascii_lowercase() } else { l.to_ascii_uppercase() }));
+                    },
+                    None => empty_run += 1
+                }
+            }
+            if empty_run > 0 {
+                row.push_str(&empty_run.to_string());
+            }
+            rows.push(row);
+        }
+        rows.join("/")
+    }
+
+    ///
+    /// Reads a standard board written by to_board_string
+    pub fn from_board_string(board_string: &str) -> Result<ScrabbleBoard, String> {
+        let ncols = ScrabbleBoard::empty_scrabble_board().ncols();
+        let mut text = String::new();
+        for (i, row) in board_string.split('/').enumerate() {
+            let mut empty_run = 0;
+            for c in row.chars() {
+                match c.to_digit(10) {
+                    Some(d) => {
+                        empty_run = empty_run * 10 + d as usize;
+                        if empty_run > ncols {
+                            return Err(format!("Row {} has more than {} squares", i + 1, ncols));
+                        }
+                    },
+                    None => {
+                        text.extend(std::iter::repeat(EMPTY_SQUARE_TEXT).take(empty_run));
+                        empty_run = 0;
+                        text.push(c);
+                    }
+                }
+            }
+            text.extend(std::iter::repeat(EMPTY_SQUARE_TEXT).take(empty_run));
+            text.push('\n');
+        }
+        Self::from_text(&text)
+    }
+
+    ///
+    /// Reads a standard board written by to_text
+    pub fn from_text(text: &str) -> Result<ScrabbleBoard, String> {
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        let rows: Vec<&str> = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+        if rows.len() != board.nrows() {
+            return Err(format!("A board has {} rows, not {}", board.nrows(), rows.len()));
+        }
+
+        for (i, row) in rows.iter().enumerate() {
+            if row.chars().count() != board.ncols() {
+                return Err(format!("Row {} has {} squares, not {}", i + 1, row.chars().count(), board.ncols()));
+            }
+            for (j, c) in row.chars().enumerate() {
+                let coord = Coord::new(i as i32, j as i32);
+                match c {
+                    EMPTY_SQUARE_TEXT => (),
+                    'A'..='Z' => board.set_letter_unchecked(coord, c.to_ascii_lowercase() as Letter),
+                    'a'..='z' => board.set_letter_unchecked(coord, c.to_ascii_uppercase() as Letter),
+                    _ => return Err(format!("{} on row {} is not a letter or {}", c, i + 1, EMPTY_SQUARE_TEXT))
+                }
+            }
+        }
+        Ok(board)
+    }
+
     pub fn print_board(&self) {
         for i in 0..self.nrows() {
             for j in 0..self.ncols() {
@@ -255,6 +498,192 @@ impl ScrabbleBoard {
         }
     }
 
+    ///
+    /// Puts the tiles of a validated play on the board
+    pub fn place_play(&mut self, validation: &PlayValidation) {
+        let mut coord = validation.start_coord;
+        for l in validation.word.bytes() {
+            if validation.placed.contains(&coord) {
+                self.set_letter_unchecked(coord, l);
+            }
+            coord = coord.next(validation.direction);
+        }
+    }
+
+    pub fn get_letter(&self, coord: Coord) -> Option<Letter> {
+        self.letters.get(coord).unwrap_or(None)
+    }
+
+    ///
+    /// Squares holding a tile that before doesn't have there, e.g. the tiles of the last play
+    pub fn new_tiles(&self, before: &ScrabbleBoard) -> Vec<Coord> {
+        let mut coords = Vec::new();
+        for row in 0..self.nrows() as i32 {
+            for col in 0..self.ncols() as i32 {
+                let coord = Coord::new(row, col);
+                if self.get_letter(coord).is_some() && self.get_letter(coord) != before.get_letter(coord) {
+                    coords.push(coord);
+                }
+            }
+        }
+        coords
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..self.nrows() as i32)
+            .all(|row| (0..self.ncols() as i32).all(|col| !self.has_letter_at_coord(Coord::new(row, col))))
+    }
+
+    ///
+    /// Checks a play of word (lowercase for tiles, capitals for blanks, including any letters
+    /// already on the board it goes through) starting at start_coord. Errors if the play can't
+    /// be made on this board at all, otherwise reports every word formed and whether dict has it
+    pub fn validate_play(&self,
+                         start_coord: Coord,
+                         dir: Direction,
+                         word: &str,
+                         dict: &DictionaryTrie) -> Result<PlayValidation, String> {
+        let end_coord = word.bytes().fold(start_coord, |coord, _| coord.next(dir));
+        if self.has_letter_at_previous_coord(start_coord, dir) || self.has_letter_at_coord(end_coord) {
+            return Err(String::from("The play must include the letters on either side of it"));
+        }
+
+        let mut played_board = self.clone();
+        let mut placed = Vec::new();
+        let mut coord = start_coord;
+        for l in word.bytes() {
+            if !self.is_coord_in_bounds(coord) {
+                return Err(format!("{} does not fit on the board", word));
+            }
+            if !l.is_ascii_alphabetic() {
+                return Err(format!("{} is not a letter", char::from(l)));
+            }
+            match self.get_letter(coord) {
+                Some(existing) if existing.to_ascii_lowercase() != l.to_ascii_lowercase() => {
+                    return Err(format!("{} is already at {},{}", char::from(existing), coord.row, coord.col));
+                },
+                Some(_) => (),
+                None => {
+                    played_board.set_letter_unchecked(coord, l);
+                    placed.push(coord);
+                }
+            }
+            coord = coord.next(dir);
+        }
+
+        if placed.is_empty() {
+            return Err(String::from("The play places no tiles"));
+        }
+        let connected = if self.is_empty() {
+            placed.iter().any(|&c| self.is_middle(c))
+        } else {
+            placed.len() < word.len() || placed.iter().any(|&c| {
+                self.has_letter_at_coord(c.prev(dir.rotate())) || self.has_letter_at_coord(c.next(dir.rotate()))
+            })
+        };
+        if !connected {
+            return Err(String::from("The play must connect to the letters on the board, or cover the middle square"));
+        }
+
+        let mut words = Vec::new();
+        words.extend(played_board.formed_word(start_coord, dir, &placed, dict));
+        for &c in placed.iter() {
+            words.extend(played_board.formed_word(c, dir.rotate(), from_ref(&c), dict));
+        }
+        if words.is_empty() {
+            return Err(String::from("The play must form a word of at least two letters"));
+        }
+
+        let bingo_bonus = if placed.len() >= 7 { 50 } else { 0 };
+        Ok(PlayValidation {
+            word: played_board.word_along(start_coord, dir),
+            start_coord,
+            direction: dir,
+            score: words.iter().map(|w| w.score).sum::<u32>() + bingo_bonus,
+            placed,
+            words,
+        })
+    }
+
+    //The letters from coord up to the next empty square
+    fn word_along(&self, coord: Coord, dir: Direction) -> Word {
+        let mut letters = Vec::new();
+        let mut coord = coord;
+        while let Some(l) = self.get_letter(coord) {
+            letters.push(l);
+            coord = coord.next(dir);
+        }
+        String::from_utf8(letters).unwrap()
+    }
+
+    //The same play as solution, a single tile placed going down, seen as the across word
+    // it forms, which scores the same
+    fn across_twin(&self, solution: &ScrabbleSolution) -> ScrabbleSolution {
+        let mut placed = solution.start_coord;
+        let mut letter = 0;
+        let mut coord = solution.start_coord;
+        for l in solution.word.bytes() {
+            if self.get_letter(coord).is_none() {
+                placed = coord;
+                letter = l;
+            }
+            coord = coord.next(solution.direction);
+        }
+
+        let mut start_coord = placed;
+        while self.has_letter_at_previous_coord(start_coord, Direction::Right) {
+            start_coord = start_coord.prev(Direction::Right);
+        }
+        let mut word = self.word_along(start_coord, Direction::Right);
+        word.push(char::from(letter));
+        word.push_str(&self.word_along(placed.next(Direction::Right), Direction::Right));
+        ScrabbleSolution { word, direction: Direction::Right, start_coord, ..solution.clone() }
+    }
+
+    //The word of two or more letters in dir through coord, letter and word
+    // modifiers counting only on the squares just placed
+    fn formed_word(&self,
+                   coord: Coord,
+                   dir: Direction,
+                   placed: &[Coord],
+                   dict: &DictionaryTrie) -> Option<FormedWord> {
+        let mut start_coord = coord;
+        while self.has_letter_at_previous_coord(start_coord, dir) {
+            start_coord = start_coord.prev(dir);
+        }
+        let word = self.word_along(start_coord, dir);
+        if word.len() < 2 {
+            return None;
+        }
+
+        let mut letter_score = 0;
+        let mut word_multiplier = 1;
+        let mut square = start_coord;
+        for l in word.bytes() {
+            let base_letter_score = scrabble_letter_score(l);
+            letter_score += if !placed.contains(&square) {
+                base_letter_score
+            } else {
+                match self.modifiers.get_unchecked(square) {
+                    Some(Modifier::DoubleLetter) => 2 * base_letter_score,
+                    Some(Modifier::TripleLetter) => 3 * base_letter_score,
+                    Some(Modifier::DoubleWord) => { word_multiplier *= 2; base_letter_score },
+                    Some(Modifier::TripleWord) => { word_multiplier *= 3; base_letter_score },
+                    None => base_letter_score
+                }
+            };
+            square = square.next(dir);
+        }
+
+        Some(FormedWord {
+            is_valid: dict.is_word_string(&word),
+            score: letter_score * word_multiplier,
+            word,
+            start_coord,
+            direction: dir,
+        })
+    }
+
     fn is_middle(&self, coord: Coord) -> bool {
         return coord.row == (self.nrows() as i32) / 2
             && coord.col == (self.ncols() as i32) / 2;
@@ -281,14 +710,17 @@ impl ScrabbleBoard {
                         dir: Direction,
                         num_letters: u32) -> bool {
         let mut letters_left = num_letters;
+        let mut passed_letter = false;
         let mut coord = start_coord;
         while self.is_coord_in_bounds(coord) && (letters_left>0 || self.has_letter_at_coord(coord)) {
             if self.has_letter_at_coord(coord) {
                 if letters_left < num_letters {
                     return true;
                 }
+                passed_letter = true;
             } else {
-                if self.is_middle(coord) {
+                //A tile placed after the letters the word starts on extends them
+                if passed_letter || self.is_middle(coord) {
                     return true;
                 }
 
@@ -322,60 +754,151 @@ impl ScrabbleBoard {
                                 letters_available:&LetterBag,
                                 dict: &DictionaryTrie) -> Vec<ScrabbleSolution> {
         let mut all_solutions = Vec::new();
+        self.for_each_valid_word(letters_available,
+                                 dict,
+                                 &mut |solution: ScrabbleSolution| all_solutions.push(solution));
+        all_solutions
+    }
+
+    ///
+    /// Runs the word search, handing every solution to sink as soon as it is found
+    /// instead of collecting them
+    pub fn for_each_valid_word(&self,
+                               letters_available:&LetterBag,
+                               dict: &DictionaryTrie,
+                               sink: &mut dyn SolutionSink) {
+        self.search(letters_available, dict, &SolveQuery::default(), sink);
+    }
+
+    ///
+    /// Like for_each_valid_word, but only hands sink the solutions accepted by query.
+    /// When blanks are collapsed, nothing reaches sink until the search is over
+    pub fn for_each_queried_valid_word(&self,
+                                       letters_available:&LetterBag,
+                                       dict: &DictionaryTrie,
+                                       query: &SolveQuery,
+                                       sink: &mut dyn SolutionSink) {
+        if query.collapse_blanks {
+            let mut best_designations = BestBlankDesignations::new(sink);
+            self.search(letters_available, dict, query, &mut best_designations);
+            best_designations.finish();
+        } else {
+            self.search(letters_available, dict, query, sink);
+        }
+    }
+
+    fn search(&self,
+              letters_available:&LetterBag,
+              dict: &DictionaryTrie,
+              query: &SolveQuery,
+              sink: &mut dyn SolutionSink) {
         for i in 0..self.nrows() {
             for j in 0..self.ncols() {
                 for dir in &[Direction::Right, Direction::Down] {
-                    let solutions =
-                        self.find_valid_words_coord(Coord::new(i as i32,j as i32),
-                                                    *dir,
-                                                    letters_available.clone(),
-                                                    dict);
-                    all_solutions.extend(solutions);
+                    if sink.is_done() {
+                        return;
+                    }
+                    let coord = Coord::new(i as i32,j as i32);
+                    if query.allows_start(coord, *dir) {
+                        self.for_each_valid_word_coord(coord,
+                                                       *dir,
+                                                       letters_available.clone(),
+                                                       dict,
+                                                       query,
+                                                       sink);
+                    }
                 }
             }
         }
-        all_solutions
     }
 
+    ///
+    /// Finds the solutions accepted by query, and returns the limit best ones by ranking
+    /// after skipping the first offset. Only offset+limit solutions are kept in memory
+    pub fn find_top_valid_words(&self,
+                                letters_available:&LetterBag,
+                                dict: &DictionaryTrie,
+                                query: &SolveQuery,
+                                ranking: SolutionRanking,
+                                offset: usize,
+                                limit: usize) -> SolutionsPage {
+        let mut top_solutions = TopSolutions::ranked_by(offset.saturating_add(limit), ranking);
+        let mut total = 0;
+        self.for_each_queried_valid_word(letters_available,
+                                         dict,
+                                         query,
+                                         &mut |solution: ScrabbleSolution| {
+                                             total += 1;
+                                             top_solutions.push(solution);
+                                         });
+
+        let solutions = top_solutions.into_sorted_vec()
+            .into_iter()
+            .skip(offset)
+            .collect();
+        SolutionsPage { total, solutions }
+    }
+
+    #[cfg(test)]
     fn find_valid_words_coord(&self,
                               coord: Coord,
                               dir: Direction,
                               letters_available: LetterBag,
                               dict: &DictionaryTrie) -> Vec<ScrabbleSolution> {
+        let mut solutions = Vec::new();
+        self.for_each_valid_word_coord(coord,
+                                       dir,
+                                       letters_available,
+                                       dict,
+                                       &SolveQuery::default(),
+                                       &mut |solution: ScrabbleSolution| solutions.push(solution));
+        solutions
+    }
+
+    fn for_each_valid_word_coord(&self,
+                                 coord: Coord,
+                                 dir: Direction,
+                                 letters_available: LetterBag,
+                                 dict: &DictionaryTrie,
+                                 query: &SolveQuery,
+                                 sink: &mut dyn SolutionSink) {
 
-        let prev_coord = coord.prev(dir);
         if !self.is_valid_starting_point(coord,
                                          dir,
                                          letters_available.size()) {
-            return Vec::new();
+            return;
         }
 
         let solution_builder
             = ScrabbleSolutionBuilder::new(letters_available, dict);
-        self.find_valid_words_coord_helper(coord, dir, solution_builder)
+        self.find_valid_words_coord_helper(coord, dir, solution_builder, query, sink)
     }
 
     fn find_valid_words_coord_helper(&self,
                                      coord: Coord,
                                      dir: Direction,
-                                     solution_so_far: ScrabbleSolutionBuilder) -> Vec<ScrabbleSolution> {
+                                     solution_so_far: ScrabbleSolutionBuilder,
+                                     query: &SolveQuery,
+                                     sink: &mut dyn SolutionSink) {
 
-        let mut solutions = Vec::new();
+        if sink.is_done() {
+            return;
+        }
 
         if !self.is_coord_in_bounds(coord) {
             //if we went off the board and have formed a valid solution, add it
-            if solution_so_far.is_valid_solution() {
-                solutions.push(solution_so_far.build(dir, coord));
-            }
-            return solutions;
+            solution_so_far.report_if_valid(self, dir, coord, query, sink);
+            return;
         }
 
         match self.letters.get_unchecked(coord) {
             //No placedLetters on board at this coordinate
             None => {
                 //add the word so far as a solution since this is a blank
-                if solution_so_far.is_valid_solution() {
-                    solutions.push(solution_so_far.build(dir, coord))
+                solution_so_far.report_if_valid(self, dir, coord, query, sink);
+
+                if !query.allows_longer_than(solution_so_far.word_so_far.len()) {
+                    return;
                 }
 
                 //recurse on solutions involving available placedLetters
@@ -439,24 +962,31 @@ impl ScrabbleBoard {
                                 anchored:
                                 solution_so_far.anchored || self.is_middle(coord) || has_anchor,
                                 letters_placed: solution_so_far.letters_placed + 1,
+                                cross_words:
+                                    solution_so_far.cross_words + if has_anchor { 1 } else { 0 },
                                 addon_score: solution_so_far.addon_score + addon_score,
                                 letter_score: solution_so_far.letter_score + added_letter_score,
                                 word_multiplier:
                                     solution_so_far.word_multiplier * extra_word_multiplier,
                             };
 
-                            let next_solutions = self.find_valid_words_coord_helper(
+                            self.find_valid_words_coord_helper(
                                 coord.next(dir),
                                 dir,
                                 next_solution_builder,
+                                query,
+                                sink,
                             );
-                            solutions.extend(next_solutions);
                         }
                     }
                 }
             }
             Some(l) => {
                 //There is an existing letter here, recurse on it
+                if !query.allows_longer_than(solution_so_far.word_so_far.len()) {
+                    return;
+                }
+
                 let next_trie_child =
                     solution_so_far.get_trie_child(l);
 
@@ -470,22 +1000,22 @@ impl ScrabbleBoard {
                         letters_available: solution_so_far.letters_available.clone(),
                         anchored: true,
                         letters_placed: solution_so_far.letters_placed,
+                        cross_words: solution_so_far.cross_words,
                         addon_score: solution_so_far.addon_score,
                         letter_score: solution_so_far.letter_score + scrabble_letter_score(l),
                         word_multiplier: solution_so_far.word_multiplier,
                     };
 
-                    let next_solutions = self.find_valid_words_coord_helper(
+                    self.find_valid_words_coord_helper(
                         coord.next(dir),
                         dir,
                         next_solution_builder,
+                        query,
+                        sink,
                     );
-                    solutions.extend(next_solutions);
                 }
             }
         }
-
-        solutions
     }
 
     fn validate_word_around(&self,
@@ -613,6 +1143,236 @@ mod tests {
 
     }
 
+    #[test]
+    fn find_top_words_pages() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,7), Direction::Right,"dog");
+        let letters = LetterBag::from_string("aest");
+
+        let mut all_solutions = board.find_all_valid_words(&letters, &dict);
+        all_solutions.sort_by(compare_solutions);
+
+        let query = SolveQuery::default();
+        let ranking = SolutionRanking::Score;
+        let first_page = board.find_top_valid_words(&letters, &dict, &query, ranking, 0, 5);
+        let second_page = board.find_top_valid_words(&letters, &dict, &query, ranking, 5, 5);
+        assert_eq!(first_page.total, all_solutions.len());
+        assert_eq!(first_page.solutions.len(), 5);
+
+        let paged_words: Vec<&Word> = first_page.solutions.iter()
+            .chain(second_page.solutions.iter())
+            .map(|s| &s.word)
+            .collect();
+        let expected_words: Vec<&Word> = all_solutions.iter()
+            .take(10)
+            .map(|s| &s.word)
+            .collect();
+        assert_eq!(paged_words, expected_words);
+
+        let query = SolveQuery { min_length: Some(5), ..SolveQuery::default() };
+        let long_words = board.find_top_valid_words(&letters, &dict, &query, ranking, 0, 1000);
+        assert!(long_words.solutions.iter().all(|s| s.word.len() >= 5));
+        assert_eq!(long_words.total, long_words.solutions.len());
+    }
+
+    #[test]
+    fn extends_existing_word() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,7), Direction::Right, "dog");
+
+        let solutions = board.find_all_valid_words(&LetterBag::from_string("s"), &dict);
+        let num_dogs = solutions.iter()
+            .filter(|s| s.word == "dogs" && s.start_coord == Coord::new(7,7))
+            .count();
+        assert_eq!(num_dogs, 1);
+    }
+
+    #[test]
+    fn single_tile_plays_reported_once() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,7), Direction::Right, "dog");
+        board.add_word(Coord::new(6,10), Direction::Right, "a");
+
+        let solutions = board.find_all_valid_words(&LetterBag::from_string("s"), &dict);
+        let s_plays: Vec<&ScrabbleSolution> = solutions.iter()
+            .filter(|s| (s.word == "dogs" && s.start_coord == Coord::new(7,7))
+                || (s.word == "as" && s.start_coord == Coord::new(6,10)
+                    && s.direction == Direction::Down))
+            .collect();
+        assert_eq!(s_plays.len(), 1);
+        assert_eq!(s_plays[0].word, "dogs");
+        assert_eq!(s_plays[0].score, 8);
+
+        //Each o forms do one way and ox the other, the down twin counts once the across one is filtered out
+        let dict = DictionaryTrie::from_word_list("do ox");
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,7), Direction::Right, "d");
+        board.add_word(Coord::new(8,8), Direction::Right, "x");
+        let letters = LetterBag::from_string("o");
+        for (arg, total) in [("dir=r", 2), ("dir=d", 2), ("regex=^do$", 2), ("row=8", 1)].iter() {
+            let mut query = SolveQuery::default();
+            query.parse_restriction(arg).unwrap();
+            let page = board.find_top_valid_words(&letters, &dict, &query, SolutionRanking::Score, 0, 10);
+            assert_eq!(page.total, *total, "{}", arg);
+        }
+        assert_eq!(board.find_all_valid_words(&letters, &dict).len(), 2);
+    }
+
+    #[test]
+    fn collapse_blank_designations() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,7), Direction::Right, "dog");
+        let letters = LetterBag::from_string("s*");
+        let is_dogs = |s: &&ScrabbleSolution|
+            s.word.to_ascii_lowercase() == "dogs" && s.start_coord == Coord::new(7,7);
+
+        let all_solutions = board.find_all_valid_words(&letters, &dict);
+        assert_eq!(all_solutions.iter().filter(is_dogs).count(), 2);
+
+        let query = SolveQuery { collapse_blanks: true, ..SolveQuery::default() };
+        let page = board.find_top_valid_words(&letters, &dict, &query, SolutionRanking::Score, 0, 10000);
+        let dogs: Vec<&ScrabbleSolution> = page.solutions.iter().filter(is_dogs).collect();
+        assert_eq!(dogs.len(), 1);
+        assert_eq!(dogs[0].word, "dogs");
+        assert!(page.total < all_solutions.len());
+    }
+
+    struct FirstSolutions {
+        wanted: usize,
+        solutions: Vec<ScrabbleSolution>,
+    }
+
+    impl SolutionSink for FirstSolutions {
+        fn accept(&mut self, solution: ScrabbleSolution) {
+            self.solutions.push(solution);
+        }
+
+        fn is_done(&self) -> bool {
+            self.solutions.len() >= self.wanted
+        }
+    }
+
+    #[test]
+    fn search_stops_when_sink_is_done() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,5), Direction::Right, "lolcatz");
+        let letters = LetterBag::from_string("*saebd");
+        assert!(board.find_all_valid_words(&letters, &dict).len() > 3);
+
+        for collapse_blanks in &[false, true] {
+            let query = SolveQuery { collapse_blanks: *collapse_blanks, ..SolveQuery::default() };
+            let mut first = FirstSolutions { wanted: 3, solutions: Vec::new() };
+            board.for_each_queried_valid_word(&letters, &dict, &query, &mut first);
+            assert_eq!(first.solutions.len(), 3);
+        }
+    }
+
+    #[test]
+    fn queried_search_matches_filtered_search() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,5), Direction::Right, "lolcatz");
+        board.add_word(Coord::new(6,6), Direction::Down, "goalie");
+        let letters = LetterBag::from_string("*saebd");
+        //Every play in each direction it forms a word, single tiles twice
+        let mut all_solutions = Vec::new();
+        for dir in &["dir=r", "dir=d"] {
+            let mut query = SolveQuery::default();
+            query.parse_restriction(dir).unwrap();
+            board.for_each_queried_valid_word(&letters, &dict, &query,
+                                              &mut |solution: ScrabbleSolution| all_solutions.push(solution));
+        }
+        let placement = |s: &ScrabbleSolution| {
+            let mut tiles = Vec::new();
+            let mut coord = s.start_coord;
+            for l in s.word.bytes() {
+                if board.get_letter(coord).is_none() {
+                    tiles.push((coord, l));
+                }
+                coord = coord.next(s.direction);
+            }
+            tiles
+        };
+
+        let restrictions = [vec!["through=7,7"], vec!["max_len=3", "dir=d"],
+                            vec!["row=8"], vec!["min_tiles=5"], vec!["col=6", "has=s"],
+                            vec!["regex=^b", "min_len=5"], vec!["dir=d"]];
+        for args in restrictions.iter() {
+            let mut query = SolveQuery::default();
+            for arg in args.iter() {
+                query.parse_restriction(arg).unwrap();
+            }
+
+            //A play is found once if the query accepts it in either direction
+            let mut expected = Vec::new();
+            for s in all_solutions.iter() {
+                if query.allows_start(s.start_coord, s.direction) && query.accepts(s)
+                    && !expected.contains(&placement(s)) {
+                    expected.push(placement(s));
+                }
+            }
+            let expected = expected.len();
+            let page = board.find_top_valid_words(&letters, &dict, &query,
+                                                  SolutionRanking::Score, 0, 0);
+            assert!(expected > 0);
+            assert_eq!(page.total, expected);
+        }
+    }
+
+    #[test]
+    fn validate_plays() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        assert!(board.validate_play(Coord::new(0,0), Direction::Right, "dog", &dict).is_err());
+        let play = board.validate_play(Coord::new(7,7), Direction::Right, "dOg", &dict).unwrap();
+        assert!(play.is_valid());
+        assert_eq!(play.score, 8);
+        assert_eq!(play.tiles_used(), b"d*g".to_vec());
+        board.add_word(Coord::new(7,7), Direction::Right, "dOg");
+
+        //Agrees with the search on the score of a play
+        let play = board.validate_play(Coord::new(7,7), Direction::Right, "dogs", &dict).unwrap();
+        assert_eq!(play.placed.len(), 1);
+        assert_eq!(play.word, "dOgs");
+        assert_eq!(play.score, 5);
+
+        let play = board.validate_play(Coord::new(8,8), Direction::Right, "xz", &dict).unwrap();
+        let phonies: Vec<&str> = play.phonies().iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(phonies, vec!["xz", "gz"]);
+        assert_eq!(play.words.len(), 3);
+        assert!(!play.is_valid());
+
+        assert!(board.validate_play(Coord::new(7,8), Direction::Right, "og", &dict).is_err());
+        assert!(board.validate_play(Coord::new(7,7), Direction::Right, "dig", &dict).is_err());
+        assert!(board.validate_play(Coord::new(0,0), Direction::Right, "at", &dict).is_err());
+        assert!(board.validate_play(Coord::new(7,7), Direction::Right, "dog", &dict).is_err());
+
+        assert_eq!(board.remove_letter(Coord::new(7,9)), Some(b'g'));
+        assert_eq!(board.remove_letter(Coord::new(7,9)), None);
+        assert_eq!(board.remove_letter(Coord::new(15,0)), None);
+        assert!(board.validate_play(Coord::new(7,7), Direction::Right, "dOt", &dict).unwrap().is_valid());
+    }
+
+    #[test]
+    fn validated_plays_match_search() {
+        let dict = DictionaryTrie::from_scrabble_ospd();
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7,5), Direction::Right, "lolcatz");
+        board.add_word(Coord::new(6,6), Direction::Down, "goalie");
+        for solution in board.find_all_valid_words(&LetterBag::from_string("*saebd"), &dict) {
+            let play = board.validate_play(solution.start_coord, solution.direction, &solution.word, &dict)
+                .unwrap();
+            assert!(play.is_valid());
+            assert_eq!(play.score, solution.score, "{}", solution);
+            assert_eq!(play.placed.len() as u32, solution.tiles_placed);
+        }
+    }
+
     #[test]
     fn test_scrabble_letters_score() {
         let score1 =
@@ -660,6 +1420,10 @@ mod tests {
         assert!(board.can_reach_anchor(Coord::new(6,7),
                                        Direction::Right,
                                        1));
+
+        assert!(board.can_reach_anchor(Coord::new(7,5),
+                                       Direction::Right,
+                                       1));
     }
 
 
@@ -1,86 +1,741 @@
-extern crate time;
-
-use std::io;
-use time::Instant;
+use serde::Serialize;
+use std::env;
+use std::io::{self, Read};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use ScrabbleSolver::{DictionaryTrie,
+                     Definitions,
+                     Lexicon,
+                     LexiconSet,
+                     LeaveTable,
+                     ScrabbleSolution,
                      ScrabbleBoard,
+                     Position,
                      Coord,
                      Direction,
                      LetterBag,
-                     print_top_solutions};
+                     SolveQuery,
+                     SolutionRanking,
+                     WordPattern,
+                     TileDistribution,
+                     BoardRenderer};
+use ScrabbleSolver::quiz::{self, CardBox};
+use ScrabbleSolver::gcg::GcgGame;
+
+const DEFAULT_CARDBOX_PATH: &str = "cardbox.txt";
+const DEFAULT_SOLVE_TOP: usize = 10;
+const DEFAULT_QUIZ_BAND: (usize, usize) = (1, 100);
+
+const USAGE: &str = "\
+usage: ScrabbleSolver [--lexicon NAME] [COMMAND]
+commands:
+    repl                    #interactive commands, the default; type help for a list
+    solve --board FILE [--rack RACK] [--top N] [--rank score|equity] [--format text|json] [RESTRICTION ...]
+                            #the top N (default 10) plays for a position file (- reads it from stdin),
+                            # using its rack unless --rack is given; restrictions are as for the repl's top
+    check [--format text|json] WORD ...
+                            #whether each word is valid
+    anagram LETTERS [sub|plus] [prob]
+                            #words using all letters (* for blanks), some of them, or all plus one more";
+
+#[derive(Serialize)]
+struct SolutionOutput {
+    #[serde(flatten)]
+    solution: ScrabbleSolution,
+    //Standard notation e.g. 8H
+    position: String,
+}
+
+#[derive(Serialize)]
+struct SolveOutput {
+    total: usize,
+    solutions: Vec<SolutionOutput>,
+}
+
+#[derive(Serialize)]
+struct CheckOutput<'a> {
+    word: &'a str,
+    valid: bool,
+}
+
+fn seconds_since_epoch() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn parse_band(band: &str) -> Option<(usize, usize)> {
+    let mut bounds = band.splitn(2, '-');
+    match (bounds.next().map(|b| b.parse()), bounds.next().map(|b| b.parse())) {
+        (Some(Ok(first)), Some(Ok(last))) if first <= last => Some((first, last)),
+        _ => None
+    }
+}
+
+///
+/// Asks for the anagrams of alphagrams of length len until there are none due or new,
+/// or the player answers quit. The cardbox is saved after every answer
+fn run_quiz(dict: &DictionaryTrie, len: usize, band: (usize, usize), cardbox_path: &str) {
+    let mut cardbox = match CardBox::from_file(cardbox_path) {
+        Ok(cardbox) => cardbox,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    let questions = quiz::probability_band(dict, len, band.0, band.1);
+    println!("Quizzing {} alphagrams, answer with all anagrams separated by spaces, or quit", questions.len());
+    while let Some(question) = cardbox.next_question(&questions, seconds_since_epoch()) {
+        println!("{} ({} anagrams)", question.alphagram.to_ascii_uppercase(), question.answers.len());
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).map_or(true, |read| read == 0) || answer.trim() == "quit" {
+            return;
+        }
+
+        let result = quiz::grade(dict, question, &answer);
+        if result.is_perfect() {
+            println!("Correct!");
+        } else {
+            for (heading, words) in &[("Correct", &result.correct), ("Missed", &result.missed), ("Wrong", &result.wrong)] {
+                if !words.is_empty() {
+                    println!("{}: {}", heading, words.join(" "));
+                }
+            }
+        }
 
+        cardbox.record(&question.alphagram, result.is_perfect(), seconds_since_epoch());
+        if let Err(e) = cardbox.save(cardbox_path) {
+            println!("{}", e);
+        }
+    }
+    println!("No alphagrams due, come back later");
+}
+
+
+///
+/// Lines listing the anagrams of rack, options as for the anagram command
+fn anagram_lines(dict: &DictionaryTrie, rack: &str, options: &[&str]) -> Result<Vec<String>, String> {
+    let rack = LetterBag::from_rack(rack)?;
+    let by_probability = options.contains(&"prob");
+    let sort_words = |mut words: Vec<String>| {
+        if by_probability {
+            TileDistribution::standard().sort_by_probability(&mut words);
+        }
+        words
+    };
+
+    match options.iter().find(|&&o| o != "prob").cloned() {
+        None => Ok(vec![sort_words(dict.anagrams(&rack)).join(" ")]),
+        Some("sub") => Ok(vec![sort_words(dict.subanagrams(&rack)).join(" ")]),
+        Some("plus") => Ok(dict.rack_plus_one(&rack)
+            .into_iter()
+            .map(|(l, words)| format!("+{}: {}", char::from(l), sort_words(words).join(" ")))
+            .collect()),
+        Some(other) => Err(format!("Unknown anagram option {}", other))
+    }
+}
+
+fn parse_format(format: Option<String>) -> Result<bool, String> {
+    match format.as_ref().map(|s| s.as_str()) {
+        Some("text") => Ok(false),
+        Some("json") => Ok(true),
+        _ => Err(String::from("--format needs text or json"))
+    }
+}
+
+fn read_position(path: &str) -> Result<Position, String> {
+    if path == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map_err(|e| format!("Could not read position from stdin: {}", e))?;
+        Position::from_text(&text)
+    } else {
+        Position::from_file(path)
+    }
+}
+
+fn solve(dict: &DictionaryTrie, args: Vec<String>) -> Result<(), String> {
+    let mut board_path = None;
+    let mut rack = None;
+    let mut top = DEFAULT_SOLVE_TOP;
+    let mut by_equity = false;
+    let mut json = false;
+    let mut query = SolveQuery::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--board" => board_path = Some(args.next().ok_or("--board needs a position file")?),
+            "--rack" => rack = Some(args.next().ok_or("--rack needs letters")?),
+            "--top" => top = args.next().and_then(|n| n.parse().ok()).ok_or("--top needs a number")?,
+            "--rank" => by_equity = match args.next().as_ref().map(|s| s.as_str()) {
+                Some("score") => false,
+                Some("equity") => true,
+                _ => return Err(String::from("--rank needs score or equity"))
+            },
+            "--format" => json = parse_format(args.next())?,
+            restriction => query.parse_restriction(restriction)?
+        }
+    }
+
+    let position = read_position(&board_path.ok_or("solve needs --board")?)?;
+    let rack = rack.unwrap_or(position.rack);
+    if rack.is_empty() {
+        return Err(String::from("No rack given, and the position has none"));
+    }
+    let letters = LetterBag::from_rack(&rack)?;
+    let leaves = LeaveTable::single_tiles();
+    let ranking = if by_equity { SolutionRanking::Equity(&leaves) } else { SolutionRanking::Score };
+    let page = position.board.find_top_valid_words(&letters, dict, &query, ranking, 0, top);
+
+    if json {
+        let solutions = page.solutions.into_iter()
+            .map(|solution| SolutionOutput { position: solution.start_coord.notation(solution.direction), solution })
+            .collect();
+        let output = SolveOutput { total: page.total, solutions };
+        println!("{}", serde_json::to_string(&output).map_err(|e| e.to_string())?);
+    } else {
+        for solution in page.solutions.iter() {
+            println!("{}", solution);
+        }
+    }
+    Ok(())
+}
+
+///
+/// Whether each lowercase word is valid, or an error for the first that isn't made of letters
+fn check_words<'a>(dict: &DictionaryTrie, words: &'a [String]) -> Result<Vec<CheckOutput<'a>>, String> {
+    words.iter()
+        .map(|word| if word.bytes().all(|l| l.is_ascii_lowercase()) {
+            Ok(CheckOutput { word, valid: dict.is_word_string(word) })
+        } else {
+            Err(format!("{} is not a word of letters", word))
+        })
+        .collect()
+}
+
+fn check(dict: &DictionaryTrie, args: Vec<String>) -> Result<(), String> {
+    let mut json = false;
+    let mut words = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => json = parse_format(args.next())?,
+            _ => words.push(arg.to_ascii_lowercase())
+        }
+    }
+    if words.is_empty() {
+        return Err(String::from("check needs words"));
+    }
+
+    let checked = check_words(dict, &words)?;
+    if json {
+        println!("{}", serde_json::to_string(&checked).map_err(|e| e.to_string())?);
+    } else {
+        for c in checked {
+            println!("{} is {}", c.word.to_ascii_uppercase(), if c.valid { "valid" } else { "not valid" });
+        }
+    }
+    Ok(())
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut lexicon_name = None;
+    let mut command_args = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lexicon" => lexicon_name = Some(args.next().ok_or("--lexicon needs a name")?),
+            _ => command_args.push(arg)
+        }
+    }
+
+    let command = if command_args.is_empty() { String::from("repl") } else { command_args.remove(0) };
+    if command == "help" || command == "--help" {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let lexicons = LexiconSet::from_configured_file()?;
+    let lexicon = lexicons.get(lexicon_name.as_ref().map(|s| s.as_str()))?;
+    let dict = &lexicon.dict;
+    match command.as_str() {
+        "repl" => {
+            repl(lexicon);
+            Ok(())
+        },
+        "solve" => solve(dict, command_args),
+        "check" => check(dict, command_args),
+        "anagram" => {
+            let options: Vec<&str> = command_args.iter().skip(1).map(|s| s.as_str()).collect();
+            let rack = command_args.first().ok_or("anagram needs letters")?;
+            for line in anagram_lines(dict, rack, &options)? {
+                println!("{}", line);
+            }
+            Ok(())
+        },
+        other => Err(format!("Unknown command {}", other))
+    }
+}
 
 fn main() {
+    if let Err(e) = run(env::args().skip(1).collect()) {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
+
+///
+/// The boards the repl has been through, for undo and redo
+struct History {
+    boards: Vec<ScrabbleBoard>,
+    //Boards taken off by undo since the last change, the most recently undone last
+    undone: Vec<ScrabbleBoard>,
+    //How many times the current board has changed, including by undo and redo
+    changes: usize,
+}
+
+impl History {
+    fn new() -> History {
+        History { boards: vec![ScrabbleBoard::empty_scrabble_board()], undone: Vec::new(), changes: 0 }
+    }
+
+    fn current(&self) -> &ScrabbleBoard {
+        self.boards.last().unwrap()
+    }
+
+    ///
+    /// The tiles added by the last change
+    fn recent(&self) -> Vec<Coord> {
+        match self.boards.len() {
+            0 | 1 => Vec::new(),
+            n => self.boards[n - 1].new_tiles(&self.boards[n - 2])
+        }
+    }
+
+    fn push(&mut self, board: ScrabbleBoard) {
+        self.boards.push(board);
+        self.undone.clear();
+        self.changes += 1;
+    }
+
+    fn undo(&mut self) -> bool {
+        if self.boards.len() > 1 {
+            self.undone.push(self.boards.pop().unwrap());
+            self.changes += 1;
+            true
+        } else {
+            false
+        }
+    }
 
-    let dict = DictionaryTrie::from_scrabble_ospd();
+    fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(board) => {
+                self.boards.push(board);
+                self.changes += 1;
+                true
+            },
+            None => false
+        }
+    }
+}
 
-    let mut boards = Vec::new();
-    boards.push(ScrabbleBoard::empty_scrabble_board());
+///
+/// The word validate_play expects for letters typed to place, where ? before a letter marks
+/// a blank and . stands for the tile already on its square
+fn placement_word(board: &ScrabbleBoard, coord: Coord, dir: Direction, letters: &str) -> Result<String, String> {
+    let mut word = String::new();
+    let mut blank = false;
+    let mut coord = coord;
+    for c in letters.chars() {
+        match c {
+            '?' | '*' if !blank => {
+                blank = true;
+                continue;
+            },
+            '.' if !blank => {
+                let l = board.get_letter(coord).ok_or_else(|| format!("There is no tile at {}", coord.notation(dir)))?;
+                word.push(char::from(l));
+            },
+            'a'..='z' | 'A'..='Z' => {
+                let l = c.to_ascii_lowercase();
+                word.push(if blank { l.to_ascii_uppercase() } else { l });
+            },
+            _ => return Err(format!("{} is not a letter, blank or .", c))
+        }
+        blank = false;
+        coord = coord.next(dir);
+    }
+    if blank {
+        return Err(format!("{} ends with a blank marker", letters));
+    }
+    Ok(word)
+}
+
+///
+/// The board after a legal play, or why it isn't one
+fn play_on(board: &ScrabbleBoard, coord: Coord, dir: Direction, word: &str, dict: &DictionaryTrie) -> Result<ScrabbleBoard, String> {
+    let validation = board.validate_play(coord, dir, word, dict)?;
+    let phonies: Vec<String> = validation.phonies().iter().map(|w| w.word.to_ascii_uppercase()).collect();
+    if !phonies.is_empty() {
+        return Err(format!("{} not in the lexicon", phonies.join(" ")));
+    }
+    let mut played = board.clone();
+    played.place_play(&validation);
+    Ok(played)
+}
+
+///
+/// Reads commands from stdin until it ends or the quit command
+fn repl(lexicon: &Lexicon) {
+    let dict: &DictionaryTrie = &lexicon.dict;
+    //Those configured for the lexicon until a file is loaded with definitions
+    let mut definitions = lexicon.definitions.clone();
+
+    let mut history = History::new();
+    let mut renderer = BoardRenderer::for_stdout();
+    //Lowercase, '*' for blanks
+    let mut rack = String::new();
+    //The solutions the last top listed, and whether they were found for rack
+    let mut listed: Vec<ScrabbleSolution> = Vec::new();
+    let mut listed_for_rack = false;
 
     loop {
         let mut command = String::new();
-        io::stdin().read_line(&mut command);
+        if io::stdin().read_line(&mut command).map_or(true, |read| read == 0) {
+            break;
+        }
+        let changes = history.changes;
+        let rack_before = rack.clone();
         let mut parts = command.split_ascii_whitespace();
         match parts.next().unwrap_or("") {
+            "" => (),
+            "quit" | "exit" => break,
             "help" => {
-                println!("print #prints board");
-                println!("undo #undoes last letter placement");
-                println!("top letters [n] #find top words right/down for row/col with letters, optionally limiting to top n");
-                println!("place (r,d) row col letters #place letters on board");
+                println!("quit #leave, as does the end of input");
+                println!("print #prints board, highlighting the tiles of the last change");
+                println!("undo #undoes the last change to the board");
+                println!("redo #redoes the last change undone");
+                println!("rack [letters] #print the rack, or set it, ? or * for blanks");
+                println!("save path [rack] [score ...] #save the board, the rack or the one given, and optionally scores, as a position file");
+                println!("load path #load a position file saved by save, and its rack if it has one");
+                println!("position [position string] #print the board as a one line position string, or load one");
+                println!("top [letters] [n] [restrictions] #find top words for letters or the rack, optionally limiting to top n");
+                println!("    restrictions: min_len=N max_len=N min_tiles=N bingo has=LETTERS through=ROW,COL");
+                println!("                  row=N col=N dir=r|d regex=PATTERN min_score=N collapse");
+                println!("place position letters #play letters across from e.g. 8H or down from H8, checking the play is legal");
+                println!("    or place (r,d) row col letters, counting rows and columns from 0");
+                println!("    ? before a letter marks a blank, . stands for a tile already on the board e.g. place 8H d?o.s");
+                println!("show n #prints board with the nth solution listed by top laid on it");
+                println!("play n #play the nth solution listed by top, taking its tiles off the rack if it was found for the rack");
+                println!("remove square ... #take the tiles off squares e.g. remove 8H 8I");
+                println!("anagram letters [sub|plus] [prob] #words using all letters (* for blanks), some of them, or all plus one more");
+                println!("    prob lists the words most likely to be drawn first");
+                println!("pattern pattern [len=N|len=N-M] [has=letters] #words matching a pattern of letters, ?, * and [classes]");
+                println!("definitions path #load definitions from a file of word<TAB>definition lines");
+                println!("define word #whether word is valid and its definition");
+                println!("quiz length [first-last] [cardbox=path] #anagram quiz on the alphagrams ranked first to last by probability");
+                println!("replay path #replay a GCG game record checking its scores, undo steps back through its moves");
 
             },
             "print" => {
-                boards.last().unwrap().print_board();
+                renderer.recent = history.recent();
+                print!("{}", renderer.render(history.current(), None));
+            },
+            "show" => {
+                let solution = parts.next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|i| listed.get(i));
+                match solution {
+                    Some(solution) => {
+                        renderer.recent = Vec::new();
+                        print!("{}", renderer.render(history.current(), Some(solution)));
+                        println!("{}", solution);
+                    },
+                    None => { println!("Invalid show command"); }
+                }
             },
             "undo" => {
-                if boards.len() > 1 {
-                    boards.pop();
+                if !history.undo() {
+                    println!("Nothing to undo");
+                }
+            },
+            "redo" => {
+                if !history.redo() {
+                    println!("Nothing to redo");
+                }
+            },
+            "rack" => {
+                match parts.next().map(|letters| LetterBag::from_rack(letters).map(|_| letters)) {
+                    Some(Ok(letters)) => rack = letters.to_ascii_lowercase().replace('?', "*"),
+                    Some(Err(e)) => { println!("Invalid rack command: {}", e); }
+                    None => { println!("Rack: {}", rack); }
+                }
+            },
+            "save" => {
+                let path = parts.next();
+                let given_rack = parts.next().map(|rack| LetterBag::from_rack(rack).map(|_| {
+                    rack.to_ascii_lowercase().replace('?', "*")
+                }));
+                let scores: Result<Vec<i32>, _> = parts.map(|s| s.parse::<i32>()).collect();
+
+                match (path, given_rack, scores) {
+                    (Some(path), given_rack, Ok(scores)) if given_rack.as_ref().map_or(true, |r| r.is_ok()) => {
+                        let mut position = Position::new(history.current().clone());
+                        position.rack = given_rack.map_or_else(|| rack.clone(), |r| r.unwrap());
+                        position.scores = scores;
+                        if let Err(e) = position.save(path) {
+                            println!("{}", e);
+                        }
+                    },
+                    _ => { println!("Invalid save command"); }
+                }
+            },
+            "load" => {
+                match parts.next().map(Position::from_file) {
+                    Some(Ok(position)) => {
+                        if !position.rack.is_empty() {
+                            println!("Rack: {}", position.rack);
+                            rack = position.rack;
+                        }
+                        if !position.scores.is_empty() {
+                            let scores: Vec<String> = position.scores.iter().map(|s| s.to_string()).collect();
+                            println!("Scores: {}", scores.join(" "));
+                        }
+                        history.push(position.board);
+                    },
+                    Some(Err(e)) => { println!("Invalid load command: {}", e); }
+                    None => { println!("Invalid load command"); }
+                }
+            },
+            "position" => {
+                let position_string = parts.collect::<Vec<&str>>().join(" ");
+                if position_string.is_empty() {
+                    println!("{}", Position::new(history.current().clone()).to_position_string());
+                } else {
+                    match Position::from_position_string(&position_string) {
+                        Ok(position) => history.push(position.board),
+                        Err(e) => { println!("Invalid position command: {}", e); }
+                    }
                 }
             },
             "place" => {
-                let dir = parts.next().and_then( |d| match d {
-                    "r" | "R" => Some(Direction::Right),
-                    "d" | "D" => Some(Direction::Down),
+                let args: Vec<&str> = parts.collect();
+                let placement = match args.as_slice() {
+                    [position, letters] => Coord::parse_notation(position).ok()
+                        .map(|(coord, dir)| (coord, dir, *letters)),
+                    [dir, row, col, letters] => {
+                        let dir = match *dir {
+                            "r" | "R" => Some(Direction::Right),
+                            "d" | "D" => Some(Direction::Down),
+                            _ => None
+                        };
+                        let row = row.parse::<i32>().ok();
+                        let col = col.parse::<i32>().ok();
+                        match (dir, row, col) {
+                            (Some(dir), Some(row), Some(col)) => Some((Coord::new(row, col), dir, *letters)),
+                            _ => None
+                        }
+                    },
                     _ => None
-                });
-                let row = parts.next().and_then(|r| r.parse::<i32>().ok());
-                let col = parts.next().and_then(|c| c.parse::<i32>().ok());
-                let letters = parts.next();
+                };
 
+                let board = history.current();
+                let played = placement.map(|(coord, dir, letters)| {
+                    placement_word(board, coord, dir, letters).and_then(|word| play_on(board, coord, dir, &word, dict))
+                });
 
-                let coord =
-                    row.and_then(|r| col.map(|c| Coord::new(r,c)));
+                match played {
+                    Some(Ok(played)) => history.push(played),
+                    Some(Err(e)) => { println!("Invalid place command: {}", e); }
+                    None => { println!("Invalid place command"); }
+                }
 
-                let board = boards.last().unwrap();
+            },
+            "top" => {
+                let args: Vec<&str> = parts.collect();
+                //Without letters, the rack is used
+                let letters_given = args.first().map_or(false, |arg| {
+                    arg.parse::<usize>().is_err() && SolveQuery::default().parse_restriction(arg).is_err()
+                });
+                let (letters, parts) = if letters_given {
+                    (Some(args[0]), &args[1..])
+                } else {
+                    (Some(rack.as_str()).filter(|r| !r.is_empty()), &args[..])
+                };
+                let mut n = None;
+                let mut query = SolveQuery::default();
+                let mut query_error = None;
+                for arg in parts.iter() {
+                    match arg.parse::<usize>() {
+                        Ok(i) if n.is_none() => n = Some(i),
+                        _ => if let Err(e) = query.parse_restriction(arg) {
+                            query_error = Some(e);
+                        }
+                    }
+                }
 
-                match (dir, coord, letters) {
-                    (Some(dir), Some(coord), Some(letters)) => {
-                        let mut new_board = board.clone();
-                        new_board.add_word(coord, dir,letters);
-                        boards.push(new_board);
+                match (letters.map(LetterBag::from_rack), query_error) {
+                    (Some(Ok(letters)), None) => {
+                        let board = history.current();
+                        let page = board.find_top_valid_words(&letters,
+                                                              dict,
+                                                              &query,
+                                                              SolutionRanking::Score,
+                                                              0,
+                                                              n.unwrap_or(usize::MAX));
+                        for (i, solution) in page.solutions.iter().enumerate() {
+                            println!("{:>3}. {}", i + 1, solution);
+                        }
+                        listed = page.solutions;
+                        listed_for_rack = !letters_given;
+                    },
+                    (Some(Err(e)), _) | (_, Some(e)) => { println!("Invalid top command: {}", e); }
+                    _ => { println!("Invalid top command"); }
+                }
+            },
+            "play" => {
+                let solution = parts.next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|i| listed.get(i));
+                match solution {
+                    Some(solution) => {
+                        match play_on(history.current(), solution.start_coord, solution.direction, &solution.word, dict) {
+                            Ok(played) => {
+                                history.push(played);
+                                if listed_for_rack {
+                                    rack = solution.leave.clone();
+                                    println!("Rack: {}", rack);
+                                }
+                            },
+                            Err(e) => { println!("Invalid play command: {}", e); }
+                        }
+                    },
+                    None => { println!("Invalid play command"); }
+                }
+            },
+            "remove" => {
+                let squares: Result<Vec<Coord>, String> = parts.map(|square| Coord::parse_notation(square).map(|(coord, _)| coord)).collect();
+                match squares {
+                    Ok(ref squares) if !squares.is_empty() => {
+                        let mut removed = history.current().clone();
+                        match squares.iter().find(|&&coord| removed.remove_letter(coord).is_none()) {
+                            Some(empty) => { println!("Invalid remove command: there is no tile at {}", empty.notation(Direction::Right)); }
+                            None => history.push(removed)
+                        }
+                    },
+                    Ok(_) => { println!("Invalid remove command"); }
+                    Err(e) => { println!("Invalid remove command: {}", e); }
+                }
+            },
+            "anagram" => {
+                let args: Vec<&str> = parts.collect();
+                match args.split_first().map(|(rack, options)| anagram_lines(dict, rack, options)) {
+                    Some(Ok(lines)) => {
+                        for line in lines {
+                            println!("{}", line);
+                        }
+                    },
+                    Some(Err(e)) => { println!("Invalid anagram command: {}", e); }
+                    None => { println!("Invalid anagram command"); }
+                }
+            },
+            "pattern" => {
+                let spec: Vec<&str> = parts.collect();
+                match WordPattern::parse(&spec.join(" ")) {
+                    Ok(pattern) => {
+                        println!("{}", dict.pattern_matches(&pattern).join(" "));
                     },
-                    _ => { println!("Invalid place command"); }
+                    Err(e) => { println!("Invalid pattern command: {}", e); }
+                }
+            },
+            "definitions" => {
+                match parts.next().map(Definitions::from_file) {
+                    Some(Ok(loaded)) => {
+                        println!("Loaded {} definitions", loaded.len());
+                        definitions = loaded;
+                    },
+                    Some(Err(e)) => { println!("Invalid definitions command: {}", e); }
+                    None => { println!("Invalid definitions command"); }
+                }
+            },
+            "define" => {
+                match parts.next().map(|w| w.to_ascii_lowercase()) {
+                    Some(word) if word.bytes().all(|l| l.is_ascii_lowercase()) => {
+                        let validity = if dict.is_word_string(&word) { "valid" } else { "not valid" };
+                        println!("{} is {}", word.to_ascii_uppercase(), validity);
+                        if let Some(definition) = definitions.get(&word) {
+                            println!("{}", definition);
+                        }
+                    },
+                    _ => { println!("Invalid define command"); }
+                }
+            },
+            "quiz" => {
+                let len = parts.next().and_then(|l| l.parse::<usize>().ok());
+                let mut band = Some(DEFAULT_QUIZ_BAND);
+                let mut cardbox_path = DEFAULT_CARDBOX_PATH;
+                for arg in parts {
+                    if arg.starts_with("cardbox=") {
+                        cardbox_path = &arg["cardbox=".len()..];
+                    } else {
+                        band = parse_band(arg);
+                    }
                 }
 
+                match (len, band) {
+                    (Some(len), Some(band)) => run_quiz(dict, len, band, cardbox_path),
+                    _ => { println!("Invalid quiz command"); }
+                }
             },
-            "top" => {
-                let letters = parts.next();
-                let n = parts.next().and_then(|p| p.parse::<usize>().ok());
-                match (letters, n) {
-                    (Some(letters), n) => {
-                        let letters =
-                            LetterBag::from_string(letters);
-                        let board = boards.last().unwrap();
-                        let solutions = board.find_all_valid_words(&letters,&dict);
-                        print_top_solutions(&solutions,n);
+            "replay" => {
+                match parts.next().map(|path| GcgGame::from_file(path).and_then(|game| {
+                    game.replay(dict).map(|turns| (game, turns))
+                })) {
+                    Some(Ok((game, turns))) => {
+                        for (m, turn) in game.moves.iter().zip(turns) {
+                            let phonies: Vec<String> = turn.validation.iter()
+                                .flat_map(|v| v.phonies().into_iter().map(|w| w.word.to_ascii_uppercase()))
+                                .collect();
+                            if phonies.is_empty() {
+                                println!("{}", m);
+                            } else {
+                                println!("{} #phony {}", m, phonies.join(" "));
+                            }
+                            history.push(turn.board);
+                        }
                     },
-                    _ => { println!("Invalid place command"); }
+                    Some(Err(e)) => { println!("Invalid replay command: {}", e); }
+                    None => { println!("Invalid replay command"); }
                 }
             },
             s => {
                 println!("Unknown command: {}", s);
             }
         }
+
+        //Listed solutions only hold for the board and rack they were found for
+        if history.changes != changes || rack != rack_before {
+            listed.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_only_words_of_letters() {
+        let dict = DictionaryTrie::from_word_list("can cant");
+        let words = vec![String::from("can"), String::from("cans")];
+        let checked: Vec<bool> = check_words(&dict, &words).unwrap().iter().map(|c| c.valid).collect();
+        assert_eq!(checked, vec![true, false]);
+
+        assert!(check_words(&dict, &[String::from("can't")]).is_err());
+        assert!(check_words(&dict, &[String::from("q1")]).is_err());
     }
-}
\ No newline at end of file
+}
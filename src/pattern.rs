@@ -0,0 +1,223 @@
+use super::util::Letter;
+
+const ALL_LETTERS: u32 = (1 << 26) - 1;
+//Positions in a pattern are tracked as bits of a u64
+const MAX_PATTERN_TOKENS: usize = 63;
+
+#[derive(Clone, Copy)]
+enum PatternToken {
+    //One letter out of a set, each bit standing for a letter of the alphabet
+    Letter(u32),
+    //Any run of letters, possibly empty
+    Run,
+}
+
+///
+/// A word search pattern e.g. "c?t*", "[aeiou]??s", "*q[^u]* len=4-8 has=e".
+///  letters match themselves, ? matches any letter, * matches any run of letters,
+///  [abc] matches one of the letters in brackets and [^abc] any letter but those.
+/// The pattern may be followed by length (len=5 or len=4-8) and letters (has=xyz) constraints
+#[derive(Clone)]
+pub struct WordPattern {
+    tokens: Vec<PatternToken>,
+    pub min_length: usize,
+    pub max_length: Option<usize>,
+    //Letters the word must contain, repeated letters must appear that many times
+    pub must_contain: Vec<Letter>,
+}
+
+//Set of positions in the pattern reached after matching some letters
+pub type PatternStates = u64;
+
+fn letter_bit(l: Letter) -> u32 {
+    1 << (l - b'a')
+}
+
+fn parse_length_constraint(spec: &str) -> Result<(usize, Option<usize>), String> {
+    let parse = |n: &str| n.parse::<usize>()
+        .map_err(|_| format!("len needs a number or range like 4-8, got {}", spec));
+    let mut bounds = spec.splitn(2, '-');
+    match (bounds.next(), bounds.next()) {
+        (Some(min), Some(max)) => Ok((parse(min)?, Some(parse(max)?))),
+        (Some(exact), None) => {
+            let len = parse(exact)?;
+            Ok((len, Some(len)))
+        },
+        _ => Err(format!("Invalid length constraint {}", spec))
+    }
+}
+
+fn parse_tokens(pattern: &str) -> Result<Vec<PatternToken>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            'a'..='z' | 'A'..='Z' => PatternToken::Letter(letter_bit(c.to_ascii_lowercase() as u8)),
+            '?' | '.' => PatternToken::Letter(ALL_LETTERS),
+            '*' => PatternToken::Run,
+            '[' => {
+                let mut letters = 0;
+                let mut negated = false;
+                let mut closed = false;
+                for class_char in &mut chars {
+                    match class_char {
+                        ']' => {
+                            closed = true;
+                            break;
+                        },
+                        '^' if letters == 0 && !negated => negated = true,
+                        'a'..='z' | 'A'..='Z' => {
+                            letters |= letter_bit(class_char.to_ascii_lowercase() as u8)
+                        },
+                        other => return Err(format!("{} can't appear in a letter class", other))
+                    }
+                }
+                if !closed {
+                    return Err(String::from("Letter class is missing a closing ]"));
+                }
+                PatternToken::Letter(if negated { ALL_LETTERS & !letters } else { letters })
+            },
+            other => return Err(format!("{} can't appear in a pattern", other))
+        };
+        tokens.push(token);
+    }
+
+    if tokens.len() > MAX_PATTERN_TOKENS {
+        Err(format!("Patterns can be at most {} letters long", MAX_PATTERN_TOKENS))
+    } else {
+        Ok(tokens)
+    }
+}
+
+impl WordPattern {
+    pub fn parse(spec: &str) -> Result<WordPattern, String> {
+        let mut parts = spec.split_ascii_whitespace();
+        let tokens = parse_tokens(parts.next().unwrap_or(""))?;
+        let mut pattern = WordPattern { tokens, min_length: 0, max_length: None, must_contain: Vec::new() };
+
+        for constraint in parts {
+            let mut key_value = constraint.splitn(2, '=');
+            match (key_value.next(), key_value.next()) {
+                (Some("len"), Some(spec)) => {
+                    let (min, max) = parse_length_constraint(spec)?;
+                    pattern.min_length = min;
+                    pattern.max_length = max;
+                },
+                (Some("has"), Some(letters)) if letters.chars().all(|c| c.is_ascii_alphabetic()) => {
+                    pattern.must_contain = letters.to_ascii_lowercase().into_bytes();
+                },
+                _ => return Err(format!("Unknown constraint {}", constraint))
+            }
+        }
+
+        Ok(pattern)
+    }
+
+    //Adds the positions reachable without matching a letter, i.e. by skipping runs
+    fn closure(&self, states: PatternStates) -> PatternStates {
+        let mut closed = states;
+        for (i, token) in self.tokens.iter().enumerate() {
+            if let PatternToken::Run = token {
+                if closed & (1 << i) != 0 {
+                    closed |= 1 << (i + 1);
+                }
+            }
+        }
+        closed
+    }
+
+    pub fn start_states(&self) -> PatternStates {
+        self.closure(1)
+    }
+
+    ///
+    /// The positions reached from states by matching the letter with this alphabet index.
+    /// No positions means no word continuing this way can match
+    pub fn advance(&self, states: PatternStates, alpha_idx: u8) -> PatternStates {
+        let mut next = 0;
+        for (i, token) in self.tokens.iter().enumerate() {
+            if states & (1 << i) == 0 {
+                continue;
+            }
+            match token {
+                PatternToken::Letter(letters) => if letters & (1 << alpha_idx) != 0 {
+                    next |= 1 << (i + 1);
+                },
+                PatternToken::Run => next |= 1 << i
+            }
+        }
+        self.closure(next)
+    }
+
+    pub fn is_match(&self, states: PatternStates) -> bool {
+        states & (1 << self.tokens.len()) != 0
+    }
+
+    pub fn allows_longer_than(&self, len: usize) -> bool {
+        self.max_length.map_or(true, |max| len < max)
+    }
+
+    ///
+    /// Whether a word of length len, which has yet to contain missing_letters, could still match
+    pub fn could_match_with(&self, len: usize, missing_letters: usize) -> bool {
+        self.max_length.map_or(true, |max| len + missing_letters <= max)
+    }
+
+    pub fn accepts_length(&self, len: usize) -> bool {
+        len >= self.min_length && self.max_length.map_or(true, |max| len <= max)
+    }
+}
+
+///
+/// How many of the letters in must_contain are not in word
+pub fn count_missing_letters(must_contain: &[Letter], word: &[Letter]) -> usize {
+    let mut word_letters: Vec<Letter> = word.to_vec();
+    let mut missing = 0;
+    for l in must_contain.iter() {
+        match word_letters.iter().position(|w| w == l) {
+            Some(pos) => { word_letters.swap_remove(pos); },
+            None => missing += 1
+        }
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &WordPattern, word: &str) -> bool {
+        let states = word.bytes()
+            .fold(pattern.start_states(), |states, l| pattern.advance(states, l - b'a'));
+        pattern.is_match(states)
+    }
+
+    #[test]
+    fn pattern_matching() {
+        let pattern = WordPattern::parse("c?t*").unwrap();
+        assert!(matches(&pattern, "cat"));
+        assert!(matches(&pattern, "cotton"));
+        assert!(!matches(&pattern, "ct"));
+        assert!(!matches(&pattern, "scat"));
+
+        let pattern = WordPattern::parse("*[aeiou][^s]").unwrap();
+        assert!(matches(&pattern, "cat"));
+        assert!(matches(&pattern, "at"));
+        assert!(!matches(&pattern, "cats"));
+        assert!(!matches(&pattern, "as"));
+    }
+
+    #[test]
+    fn pattern_constraints() {
+        let pattern = WordPattern::parse("*q* len=4-8 has=uE").unwrap();
+        assert_eq!(pattern.min_length, 4);
+        assert_eq!(pattern.max_length, Some(8));
+        assert_eq!(pattern.must_contain, b"ue".to_vec());
+        assert_eq!(count_missing_letters(&pattern.must_contain, b"quit"), 1);
+
+        assert!(WordPattern::parse("[abc").is_err());
+        assert!(WordPattern::parse("a1").is_err());
+        assert!(WordPattern::parse("a* len=x").is_err());
+        assert!(WordPattern::parse("a* size=3").is_err());
+    }
+}
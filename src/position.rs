@@ -0,0 +1,214 @@
+use super::util::Word;
+use super::{ScrabbleBoard, WILDCARD_LETTER};
+use std::fs;
+
+//How a position's text writes a blank on the rack
+const BLANK_TEXT: char = '?';
+//Stands for a field of a position string that isn't known
+const UNKNOWN_FIELD: &str = "-";
+
+///
+/// A board with the rack to play from and the players' scores, e.g. a study position
+pub struct Position {
+    pub board: ScrabbleBoard,
+    //Lowercase, '*' for blanks
+    pub rack: Word,
+    pub scores: Vec<i32>,
+    //Index into scores of the player whose rack it is
+    pub to_move: usize,
+    //Tiles left in the bag, if known
+    pub bag_len: Option<usize>,
+}
+
+fn rack_to_text(rack: &str) -> String {
+    rack.bytes()
+        .map(|l| if l == WILDCARD_LETTER { BLANK_TEXT } else { char::from(l.to_ascii_uppercase()) })
+        .collect()
+}
+
+fn rack_from_text(rack: &str) -> Result<Word, String> {
+    rack.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' => Ok(c.to_ascii_lowercase()),
+            BLANK_TEXT | '*' => Ok(char::from(WILDCARD_LETTER)),
+            _ => Err(format!("{} is not a letter or blank", c))
+        })
+        .collect()
+}
+
+fn parse_number<T: std::str::FromStr>(field: &str, what: &str) -> Result<T, String> {
+    field.parse::<T>().map_err(|_| format!("{} is not a {}", field, what))
+}
+
+impl Position {
+    pub fn new(board: ScrabbleBoard) -> Position {
+        Position { board, rack: Word::new(), scores: Vec::new(), to_move: 0, bag_len: None }
+    }
+
+    ///
+    /// An optional header of rack, scores, turn (the player to move, counting from 1) and bag
+    /// lines, then the board as written by ScrabbleBoard::to_text e.g.
+    /// rack AEINST?
+    /// scores 120 95
+    /// turn 1
+    /// ...............
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if !self.rack.is_empty() {
+            text.push_str(&format!("rack {}\n", rack_to_text(&self.rack)));
+        }
+        if !self.scores.is_empty() {
+            let scores: Vec<String> = self.scores.iter().map(|s| s.to_string()).collect();
+            text.push_str(&format!("scores {}\n", scores.join(" ")));
+        }
+        text.push_str(&format!("turn {}\n", self.to_move + 1));
+        if let Some(bag_len) = self.bag_len {
+            text.push_str(&format!("bag {}\n", bag_len));
+        }
+        text.push_str(&self.board.to_text());
+        text
+    }
+
+    ///
+    /// Reads a position written by to_text, ignoring blank lines and # comments
+    pub fn from_text(text: &str) -> Result<Position, String> {
+        let mut position = Position::new(ScrabbleBoard::empty_scrabble_board());
+        let mut rows = Vec::new();
+        for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let mut parts = line.split_ascii_whitespace();
+            match parts.next() {
+                Some("rack") => position.rack = rack_from_text(parts.next().unwrap_or(""))?,
+                Some("scores") => {
+                    position.scores = parts.map(|s| parse_number(s, "score")).collect::<Result<Vec<i32>, String>>()?;
+                },
+                Some("turn") => position.to_move = Self::parse_turn(parts.next().unwrap_or(""))?,
+                Some("bag") => position.bag_len = Some(parse_number(parts.next().unwrap_or(""), "number of tiles")?),
+                _ => rows.push(line)
+            }
+        }
+
+        position.board = ScrabbleBoard::from_text(&rows.join("\n"))?;
+        Ok(position)
+    }
+
+    fn parse_turn(turn: &str) -> Result<usize, String> {
+        match parse_number::<usize>(turn, "player number")? {
+            0 => Err(String::from("Players are numbered from 1")),
+            player => Ok(player - 1)
+        }
+    }
+
+    ///
+    /// The position on one line for URLs and database keys, like a chess FEN: the board as written
+    /// by ScrabbleBoard::to_board_string, the rack, tiles in the bag, comma separated scores and
+    /// the player to move counting from 1, - standing for an empty or unknown field e.g.
+    /// 15/15/15/15/15/15/15/7DoG5/15/15/15/15/15/15/15 AEINST? 86 12,0 2
+    pub fn to_position_string(&self) -> String {
+        let or_unknown = |field: String| if field.is_empty() { String::from(UNKNOWN_FIELD) } else { field };
+        let scores: Vec<String> = self.scores.iter().map(|s| s.to_string()).collect();
+        format!("{} {} {} {} {}",
+                self.board.to_board_string(),
+                or_unknown(rack_to_text(&self.rack)),
+                or_unknown(self.bag_len.map_or(String::new(), |b| b.to_string())),
+                or_unknown(scores.join(",")),
+                self.to_move + 1)
+    }
+
+    ///
+    /// Reads a position string written by to_position_string. Only the board is required,
+    /// any of the fields after it can be left off
+    pub fn from_position_string(position_string: &str) -> Result<Position, String> {
+        let fields: Vec<&str> = position_string.split_ascii_whitespace().collect();
+        if fields.len() > 5 {
+            return Err(format!("{} has more than 5 fields", position_string));
+        }
+        let known = |idx: usize| fields.get(idx).cloned().filter(|&f| f != UNKNOWN_FIELD);
+
+        let mut position = Position::new(ScrabbleBoard::from_board_string(fields.first().cloned().unwrap_or(""))?);
+        if let Some(rack) = known(1) {
+            position.rack = rack_from_text(rack)?;
+        }
+        if let Some(bag_len) = known(2) {
+            position.bag_len = Some(parse_number(bag_len, "number of tiles")?);
+        }
+        if let Some(scores) = known(3) {
+            position.scores = scores.split(',').map(|s| parse_number(s, "score")).collect::<Result<Vec<i32>, String>>()?;
+        }
+        if let Some(turn) = known(4) {
+            position.to_move = Self::parse_turn(turn)?;
+        }
+        Ok(position)
+    }
+
+    pub fn from_file(path: &str) -> Result<Position, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read position {}: {}", path, e))?;
+        Self::from_text(&text)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_text()).map_err(|e| format!("Could not save position {}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Coord, Direction};
+
+    #[test]
+    fn position_text() {
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7, 6), Direction::Right, "dOg");
+        let position = Position { board, rack: String::from("aeinst*"), scores: vec![120, -5], to_move: 1, bag_len: None };
+
+        let text = position.to_text();
+        assert!(text.starts_with("rack AEINST?\nscores 120 -5\nturn 2\n"));
+        assert!(text.contains("\n......DoG......\n"));
+
+        let reread = Position::from_text(&format!("# A study position\n{}", text)).unwrap();
+        assert_eq!(reread.rack, "aeinst*");
+        assert_eq!(reread.scores, vec![120, -5]);
+        assert_eq!(reread.to_move, 1);
+        assert_eq!(reread.board.get_letter(Coord::new(7, 7)), Some(b'O'));
+        assert_eq!(reread.to_text(), text);
+
+        let no_scores = Position { scores: Vec::new(), ..Position::from_text(&text).unwrap() };
+        let reread = Position::from_text(&no_scores.to_text()).unwrap();
+        assert!(reread.scores.is_empty());
+        assert_eq!(reread.to_move, 1);
+
+        let empty = Position::from_text(&ScrabbleBoard::empty_scrabble_board().to_text()).unwrap();
+        assert!(empty.board.is_empty());
+        assert!(empty.rack.is_empty());
+
+        assert!(Position::from_text(&text.replacen("......DoG......", "......DoG.....", 1)).is_err());
+        assert!(Position::from_text(&text.replacen("......DoG......", "......D0G......", 1)).is_err());
+        assert!(Position::from_text(&text.replacen("scores 120", "scores many", 1)).is_err());
+        assert!(Position::from_text("rack AB\n...").is_err());
+    }
+
+    #[test]
+    fn position_string() {
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7, 7), Direction::Right, "dOg");
+        board.add_word(Coord::new(0, 0), Direction::Down, "za");
+        let position = Position { board, rack: String::from("aeinst*"), scores: vec![12, 0], to_move: 1, bag_len: Some(86) };
+
+        let position_string = position.to_position_string();
+        assert_eq!(position_string, "Z14/A14/15/15/15/15/15/7DoG5/15/15/15/15/15/15/15 AEINST? 86 12,0 2");
+        let reread = Position::from_position_string(&position_string).unwrap();
+        assert_eq!(reread.to_text(), position.to_text());
+        assert_eq!(reread.to_position_string(), position_string);
+
+        let board_only = Position::from_position_string("Z14/A14/15/15/15/15/15/7DoG5/15/15/15/15/15/15/15").unwrap();
+        assert_eq!(board_only.to_position_string(), "Z14/A14/15/15/15/15/15/7DoG5/15/15/15/15/15/15/15 - - - 1");
+        assert_eq!(board_only.board.get_letter(Coord::new(7, 8)), Some(b'O'));
+        assert!(Position::from_position_string(&ScrabbleBoard::empty_scrabble_board().to_board_string()).unwrap().board.is_empty());
+
+        assert!(Position::from_position_string("15/15").is_err());
+        assert!(Position::from_position_string("Z15/A14/15/15/15/15/15/7DoG5/15/15/15/15/15/15/15").is_err());
+        assert!(Position::from_position_string("99999999999999999999/15/15/15/15/15/15/15/15/15/15/15/15/15/15").is_err());
+        assert!(Position::from_position_string("Z14/A14/15/15/15/15/15/7DoG5/15/15/15/15/15/15/15 - - - 0").is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use super::util::{Letter, Word};
+use std::cmp::Ordering;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref STANDARD_DISTRIBUTION: TileDistribution =
+        TileDistribution::from_spec(include_str!("../resources/scrabble_tile_distribution.txt"))
+            .unwrap();
+}
+
+///
+/// How many tiles of each letter, and how many blanks, a game starts with
+#[derive(Clone)]
+pub struct TileDistribution {
+    counts: [u32; 26],
+    blanks: u32,
+}
+
+fn choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+impl TileDistribution {
+    pub fn standard() -> TileDistribution {
+        STANDARD_DISTRIBUTION.clone()
+    }
+
+    ///
+    /// spec: lines of <letters> <count>, '*' standing for the blank e.g.
+    /// ai  9
+    pub fn from_spec(spec: &str) -> Result<TileDistribution, String> {
+        let mut distribution = TileDistribution { counts: [0; 26], blanks: 0 };
+        for line in spec.trim().lines() {
+            let mut parts = line.split_ascii_whitespace();
+            let (letters, count) = match (parts.next(), parts.next().map(|c| c.parse::<u32>())) {
+                (Some(letters), Some(Ok(count))) => (letters, count),
+                _ => return Err(format!("{} is not in <letters> <count> format", line))
+            };
+
+            for l in letters.bytes() {
+                match l {
+                    b'a'..=b'z' => distribution.counts[(l - b'a') as usize] = count,
+                    b'*' => distribution.blanks = count,
+                    _ => return Err(format!("{} is not a letter or blank", char::from(l)))
+                }
+            }
+        }
+        Ok(distribution)
+    }
+
+    pub fn count(&self, l: Letter) -> u32 {
+        self.counts[(l.to_ascii_lowercase() - b'a') as usize]
+    }
+
+    pub fn blanks(&self) -> u32 {
+        self.blanks
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum::<u32>() + self.blanks
+    }
+
+    ///
+    /// Number of ways to draw a set of tiles spelling word from a full bag, blanks standing
+    /// in for any of its letters. Equal to the combination counts of Zyzzyva-style study tools
+    pub fn combinations(&self, word: &str) -> f64 {
+        let mut letter_counts = [0u32; 26];
+        for l in word.bytes() {
+            letter_counts[(l.to_ascii_lowercase() - b'a') as usize] += 1;
+        }
+
+        //ways_with_blanks[b] counts the draws of the letters so far that leave b of them to blanks
+        let max_blanks = self.blanks.min(word.len() as u32) as usize;
+        let mut ways_with_blanks = vec![0.0; max_blanks + 1];
+        ways_with_blanks[0] = 1.0;
+        for (idx, &needed) in letter_counts.iter().enumerate().filter(|(_, &needed)| needed > 0) {
+            let mut next = vec![0.0; max_blanks + 1];
+            for (blanks_used, &ways) in ways_with_blanks.iter().enumerate().filter(|(_, &ways)| ways > 0.0) {
+                for blanked in 0..=needed as usize {
+                    if blanks_used + blanked > max_blanks {
+                        break;
+                    }
+                    next[blanks_used + blanked] += ways * choose(self.counts[idx], needed - blanked as u32);
+                }
+            }
+            ways_with_blanks = next;
+        }
+
+        ways_with_blanks.iter()
+            .enumerate()
+            .map(|(blanks_used, ways)| ways * choose(self.blanks, blanks_used as u32))
+            .sum()
+    }
+
+    ///
+    /// Chance that a draw of word.len() tiles from a full bag spells word
+    pub fn probability(&self, word: &str) -> f64 {
+        self.combinations(word) / choose(self.total(), word.len() as u32)
+    }
+
+    ///
+    /// Sorts words most probable first, equally probable words in alphabetical order
+    pub fn sort_by_probability(&self, words: &mut Vec<Word>) {
+        let mut ranked: Vec<(f64, Word)> = words.drain(..)
+            .map(|w| (self.combinations(&w), w))
+            .collect();
+        ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal).then_with(|| a.1.cmp(&b.1)));
+        words.extend(ranked.into_iter().map(|(_, w)| w));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_distribution() {
+        let distribution = TileDistribution::standard();
+        assert_eq!(distribution.total(), 100);
+        assert_eq!(distribution.count(b'e'), 12);
+        assert_eq!(distribution.blanks(), 2);
+    }
+
+    #[test]
+    fn combinations() {
+        let distribution = TileDistribution::from_spec("a 9\nd 4").unwrap();
+        //9 A's choose 2, times 4 D's
+        assert_eq!(distribution.combinations("aad"), 144.0);
+        assert_eq!(distribution.combinations("zz"), 0.0);
+
+        let distribution = TileDistribution::standard();
+        //144 without blanks, 2 * (36 + 36) with one and 4 + 9 with both
+        assert_eq!(distribution.combinations("aad"), 301.0);
+        //The Z with one blank or both blanks
+        assert_eq!(distribution.combinations("zz"), 3.0);
+        assert_eq!(distribution.combinations("zzz"), 1.0);
+        assert_eq!(distribution.probability("zz"), 3.0 / 4950.0);
+    }
+
+    #[test]
+    fn probability_order() {
+        let distribution = TileDistribution::standard();
+        let mut words = vec![String::from("zax"), String::from("tea"), String::from("eat"), String::from("qat")];
+        distribution.sort_by_probability(&mut words);
+        assert_eq!(words, vec!["eat", "tea", "qat", "zax"]);
+    }
+}
@@ -0,0 +1,214 @@
+use super::dictionary::DictionaryTrie;
+use super::probability::TileDistribution;
+use super::util::Word;
+use super::wordlists::words_of_length;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+//Days until a card in each box is due again, cards in the last box stay there
+const BOX_INTERVAL_DAYS: [u64; 10] = [1, 3, 7, 14, 30, 60, 120, 240, 480, 960];
+//How soon a missed card comes back, so it can be retried in the same session
+const MISSED_INTERVAL_SECONDS: u64 = 10 * 60;
+
+///
+/// Sorted letters of word, which all of its anagrams share
+pub fn alphagram(word: &str) -> Word {
+    let mut letters: Vec<u8> = word.to_ascii_lowercase().into_bytes();
+    letters.sort();
+    String::from_utf8(letters).unwrap()
+}
+
+pub struct QuizQuestion {
+    pub alphagram: Word,
+    //Alphabetical order
+    pub answers: Vec<Word>,
+}
+
+pub struct QuizResult {
+    pub correct: Vec<Word>,
+    pub missed: Vec<Word>,
+    //Guesses which aren't anagrams of the alphagram in the dictionary
+    pub wrong: Vec<Word>,
+}
+
+impl QuizResult {
+    pub fn is_perfect(&self) -> bool {
+        self.missed.is_empty() && self.wrong.is_empty()
+    }
+}
+
+///
+/// Questions for the alphagrams of words of length len, ranked most probable first,
+/// keeping those ranked from first to last (1 based, inclusive)
+pub fn probability_band(dict: &DictionaryTrie, len: usize, first: usize, last: usize) -> Vec<QuizQuestion> {
+    let mut answers: BTreeMap<Word, Vec<Word>> = BTreeMap::new();
+    for word in words_of_length(dict, len) {
+        answers.entry(alphagram(&word)).or_default().push(word);
+    }
+
+    let mut alphagrams: Vec<Word> = answers.keys().cloned().collect();
+    TileDistribution::standard().sort_by_probability(&mut alphagrams);
+    alphagrams.into_iter()
+        .skip(first.saturating_sub(1))
+        .take((last + 1).saturating_sub(first.max(1)))
+        .map(|alphagram| {
+            let answers = answers.remove(&alphagram).unwrap();
+            QuizQuestion { alphagram, answers }
+        })
+        .collect()
+}
+
+///
+/// Grades whitespace separated guesses, in any case, against the dictionary
+pub fn grade(dict: &DictionaryTrie, question: &QuizQuestion, guesses: &str) -> QuizResult {
+    let mut correct = Vec::new();
+    let mut wrong = Vec::new();
+    for guess in guesses.split_ascii_whitespace().map(|g| g.to_ascii_lowercase()) {
+        if correct.contains(&guess) || wrong.contains(&guess) {
+            continue;
+        }
+        let is_answer = guess.bytes().all(|l| l.is_ascii_lowercase())
+            && alphagram(&guess) == question.alphagram
+            && dict.is_word_string(&guess);
+        if is_answer { correct.push(guess) } else { wrong.push(guess) }
+    }
+
+    let missed = question.answers.iter()
+        .filter(|answer| !correct.contains(answer))
+        .cloned()
+        .collect();
+    QuizResult { correct, missed, wrong }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Card {
+    pub box_idx: usize,
+    //Seconds since the unix epoch
+    pub due: u64,
+}
+
+///
+/// Cardbox scheduling of quiz questions: an alphagram answered perfectly moves up a box and
+/// comes back after that box's interval, a missed one goes back to the first box
+pub struct CardBox {
+    cards: HashMap<Word, Card>,
+}
+
+impl CardBox {
+    pub fn new() -> CardBox {
+        CardBox { cards: HashMap::new() }
+    }
+
+    ///
+    /// spec: one card per line as <alphagram> <box> <due>
+    pub fn from_spec(spec: &str) -> Result<CardBox, String> {
+        let mut cardbox = CardBox::new();
+        for line in spec.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let parts: Vec<&str> = line.split_ascii_whitespace().collect();
+            match parts.as_slice() {
+                [alphagram, box_idx, due] => {
+                    let card = match (box_idx.parse(), due.parse()) {
+                        (Ok(box_idx), Ok(due)) => Card { box_idx, due },
+                        _ => return Err(format!("{} is not in <alphagram> <box> <due> format", line))
+                    };
+                    cardbox.cards.insert(String::from(*alphagram), card);
+                },
+                _ => return Err(format!("{} is not in <alphagram> <box> <due> format", line))
+            }
+        }
+        Ok(cardbox)
+    }
+
+    ///
+    /// Loads a cardbox saved by save, or an empty one if the file doesn't exist yet
+    pub fn from_file(path: &str) -> Result<CardBox, String> {
+        match fs::read_to_string(path) {
+            Ok(spec) => Self::from_spec(&spec),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(CardBox::new()),
+            Err(e) => Err(format!("Could not read cardbox {}: {}", path, e))
+        }
+    }
+
+    pub fn to_spec(&self) -> String {
+        let mut alphagrams: Vec<&Word> = self.cards.keys().collect();
+        alphagrams.sort();
+        alphagrams.iter()
+            .map(|alphagram| {
+                let card = self.cards[*alphagram];
+                format!("{} {} {}\n", alphagram, card.box_idx, card.due)
+            })
+            .collect()
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_spec()).map_err(|e| format!("Could not save cardbox {}: {}", path, e))
+    }
+
+    pub fn card(&self, alphagram: &str) -> Option<Card> {
+        self.cards.get(alphagram).cloned()
+    }
+
+    pub fn record(&mut self, alphagram: &str, perfect: bool, now: u64) {
+        let card = if perfect {
+            let box_idx = self.cards.get(alphagram)
+                .map_or(0, |card| (card.box_idx + 1).min(BOX_INTERVAL_DAYS.len() - 1));
+            Card { box_idx, due: now + BOX_INTERVAL_DAYS[box_idx] * SECONDS_PER_DAY }
+        } else {
+            Card { box_idx: 0, due: now + MISSED_INTERVAL_SECONDS }
+        };
+        self.cards.insert(String::from(alphagram), card);
+    }
+
+    ///
+    /// The question to ask next: the card longest overdue, or else the first question not yet asked
+    pub fn next_question<'q>(&self, questions: &'q [QuizQuestion], now: u64) -> Option<&'q QuizQuestion> {
+        let overdue = questions.iter()
+            .filter_map(|q| self.card(&q.alphagram).map(|card| (card.due, q)))
+            .filter(|(due, _)| *due <= now)
+            .min_by_key(|(due, _)| *due)
+            .map(|(_, q)| q);
+        overdue.or_else(|| questions.iter().find(|q| self.card(&q.alphagram).is_none()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiz_questions() {
+        let dict = DictionaryTrie::from_word_list("tea eat ate zax qat cat act");
+        let questions = probability_band(&dict, 3, 1, 2);
+        assert_eq!(questions.len(), 2);
+        assert_eq!(questions[0].alphagram, "aet");
+        assert_eq!(questions[0].answers, vec!["ate", "eat", "tea"]);
+
+        let result = grade(&dict, &questions[0], "EAT tae eat eta");
+        assert_eq!(result.correct, vec!["eat"]);
+        assert_eq!(result.missed, vec!["ate", "tea"]);
+        assert_eq!(result.wrong, vec!["tae", "eta"]);
+        assert!(!result.is_perfect());
+        assert!(grade(&dict, &questions[0], "tea ate eat").is_perfect());
+    }
+
+    #[test]
+    fn cardbox_scheduling() {
+        let dict = DictionaryTrie::from_word_list("tea eat ate qat cat act");
+        let questions = probability_band(&dict, 3, 1, 3);
+        let mut cardbox = CardBox::new();
+        assert_eq!(cardbox.next_question(&questions, 0).unwrap().alphagram, "aet");
+
+        cardbox.record("aet", true, 0);
+        cardbox.record("aet", true, 0);
+        assert_eq!(cardbox.card("aet"), Some(Card { box_idx: 1, due: 3 * SECONDS_PER_DAY }));
+        let next = cardbox.next_question(&questions, 0).unwrap().alphagram.clone();
+        cardbox.record(&next, false, 0);
+        assert_eq!(cardbox.next_question(&questions, MISSED_INTERVAL_SECONDS).unwrap().alphagram, next);
+
+        let reread = CardBox::from_spec(&cardbox.to_spec()).unwrap();
+        assert_eq!(reread.card("aet"), cardbox.card("aet"));
+        assert!(CardBox::from_spec("aet 1").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use super::grid::Coord;
+use super::util::Letter;
+use super::{Modifier, ScrabbleBoard, ScrabbleSolution};
+use atty::Stream;
+
+const RESET: &str = "\x1b[0m";
+const TILE_STYLE: &str = "\x1b[1;30;43m";
+//Blanks are not bold, and their letters are lowercase as in the position formats
+const BLANK_STYLE: &str = "\x1b[31;43m";
+const RECENT_STYLE: &str = "\x1b[1;30;103m";
+const CANDIDATE_STYLE: &str = "\x1b[1;30;42m";
+const TRIPLE_WORD_STYLE: &str = "\x1b[30;41m";
+const DOUBLE_WORD_STYLE: &str = "\x1b[30;45m";
+const TRIPLE_LETTER_STYLE: &str = "\x1b[30;44m";
+const DOUBLE_LETTER_STYLE: &str = "\x1b[30;46m";
+const EMPTY_STYLE: &str = "\x1b[2m";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Highlight {
+    None,
+    Recent,
+    Candidate,
+}
+
+///
+/// Draws a board for a terminal with row numbers and column letters, in ANSI colors or in
+/// plain ASCII, where premium squares are = triple word, - double word, " triple letter and
+/// ' double letter, and highlighted tiles are bracketed
+pub struct BoardRenderer {
+    pub color: bool,
+    //Squares to pick out, e.g. the tiles of the last play
+    pub recent: Vec<Coord>,
+}
+
+impl BoardRenderer {
+    pub fn new(color: bool) -> BoardRenderer {
+        BoardRenderer { color, recent: Vec::new() }
+    }
+
+    ///
+    /// Colors only when stdout is a terminal
+    pub fn for_stdout() -> BoardRenderer {
+        Self::new(atty::is(Stream::Stdout))
+    }
+
+    ///
+    /// The board with candidate's tiles, if any, laid on it but marked as not yet played.
+    /// Tiles that would be off the board, e.g. for a solution found on another board, are left out
+    pub fn render(&self, board: &ScrabbleBoard, candidate: Option<&ScrabbleSolution>) -> String {
+        let mut candidate_board = board.clone();
+        let mut candidate_tiles = Vec::new();
+        if let Some(candidate) = candidate {
+            let mut coord = candidate.start_coord;
+            for l in candidate.word.bytes() {
+                if board.is_coord_in_bounds(coord) && board.get_letter(coord).is_none() {
+                    candidate_board.set_letter_unchecked(coord, l);
+                    candidate_tiles.push(coord);
+                }
+                coord = coord.next(candidate.direction);
+            }
+        }
+
+        let mut text = String::from("   ");
+        for col in 0..board.ncols() {
+            text.push_str(&format!(" {} ", char::from(b'A' + col as u8)));
+        }
+        text.push('\n');
+        for row in 0..board.nrows() as i32 {
+            text.push_str(&format!("{:>2} ", row + 1));
+            for col in 0..board.ncols() as i32 {
+                let coord = Coord::new(row, col);
+                let highlight = if candidate_tiles.contains(&coord) {
+                    Highlight::Candidate
+                } else if self.recent.contains(&coord) {
+                    Highlight::Recent
+                } else {
+                    Highlight::None
+                };
+                text.push_str(&self.square(&candidate_board, coord, highlight));
+            }
+            text.push_str(&format!(" {}\n", row + 1));
+        }
+        text
+    }
+
+    fn square(&self, board: &ScrabbleBoard, coord: Coord, highlight: Highlight) -> String {
+        match board.get_letter(coord) {
+            Some(l) => self.tile(l, highlight),
+            None => {
+                let (c, style) = match board.modifiers.get_unchecked(coord) {
+                    Some(Modifier::TripleWord) => ('=', TRIPLE_WORD_STYLE),
+                    Some(Modifier::DoubleWord) => ('-', DOUBLE_WORD_STYLE),
+                    Some(Modifier::TripleLetter) => ('"', TRIPLE_LETTER_STYLE),
+                    Some(Modifier::DoubleLetter) => ('\'', DOUBLE_LETTER_STYLE),
+                    None => ('.', EMPTY_STYLE)
+                };
+                self.styled(&format!(" {} ", c), style)
+            }
+        }
+    }
+
+    fn tile(&self, l: Letter, highlight: Highlight) -> String {
+        let is_blank = l.is_ascii_uppercase();
+        //Tiles are shown as capitals and blanks as lowercase
+        let c = if is_blank { l.to_ascii_lowercase() } else { l.to_ascii_uppercase() };
+        let c = char::from(c);
+        if self.color {
+            let style = match highlight {
+                Highlight::Candidate => CANDIDATE_STYLE,
+                Highlight::Recent => RECENT_STYLE,
+                Highlight::None if is_blank => BLANK_STYLE,
+                Highlight::None => TILE_STYLE
+            };
+            self.styled(&format!(" {} ", c), style)
+        } else if highlight == Highlight::None {
+            format!(" {} ", c)
+        } else {
+            format!("[{}]", c)
+        }
+    }
+
+    fn styled(&self, text: &str, style: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            String::from(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Direction;
+
+    #[test]
+    fn render_plain() {
+        let mut board = ScrabbleBoard::empty_scrabble_board();
+        board.add_word(Coord::new(7, 7), Direction::Right, "dOg");
+        let mut renderer = BoardRenderer::new(false);
+        renderer.recent = vec![Coord::new(7, 9)];
+        let candidate = ScrabbleSolution {
+            word: String::from("dOgs"),
+            score: 5,
+            direction: Direction::Right,
+            start_coord: Coord::new(7, 7),
+            leave: String::new(),
+            tiles_placed: 1,
+        };
+
+        let text = renderer.render(&board, Some(&candidate));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 16);
+        assert!(lines[0].starts_with("    A  B  C "));
+        assert!(lines[1].starts_with(" 1  =  .  .  ' "));
+        assert!(lines[8].contains(" D  o [G][S]"));
+        assert!(lines[8].ends_with(" 8"));
+        assert!(!text.contains('\x1b'));
+        assert_eq!(board.new_tiles(&ScrabbleBoard::empty_scrabble_board()).len(), 3);
+
+        let off_board = ScrabbleSolution { start_coord: Coord::new(14, 13), ..candidate.clone() };
+        let text = renderer.render(&board, Some(&off_board));
+        assert!(text.lines().nth(15).unwrap().ends_with("[D][o] 15"));
+
+        let colored = BoardRenderer::new(true).render(&board, None);
+        assert!(colored.contains(&format!("{} o {}", BLANK_STYLE, RESET)));
+        assert!(colored.contains(&format!("{} D {}", TILE_STYLE, RESET)));
+    }
+}
@@ -0,0 +1,205 @@
+use super::ScrabbleSolution;
+use super::grid::Coord;
+use super::util::{Direction, Letter};
+use regex::Regex;
+
+///
+/// Restrictions on which solutions a board search reports.
+/// Restrictions on where words go and how long they are prune the search itself,
+/// the rest are checked on each solution found.
+#[derive(Clone, Default)]
+pub struct SolveQuery {
+    pub min_score: u32,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub min_tiles_placed: Option<u32>,
+    //Letters the word must contain, repeated letters must appear that many times
+    pub must_include: Vec<Letter>,
+    //A square the word must cover, e.g. to play through a letter already on the board
+    pub must_use: Option<Coord>,
+    pub direction: Option<Direction>,
+    //Only across words on this row / down words in this column
+    pub row: Option<i32>,
+    pub col: Option<i32>,
+    //Matched against the lowercase main word
+    pub word_pattern: Option<Regex>,
+    //Report only the highest scoring choice of blank letters for each word and position
+    pub collapse_blanks: bool,
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>()
+        .map_err(|_| format!("{} needs a number, got {}", key, value))
+}
+
+impl SolveQuery {
+    ///
+    /// Applies one key=value restriction (or a lone flag), as typed on the command line e.g.
+    /// min_len=7, max_len=5, min_tiles=7, bingo, has=qu, through=7,7, row=7, col=3,
+    /// dir=r, regex=^re, min_score=30, collapse
+    pub fn parse_restriction(&mut self, arg: &str) -> Result<(), String> {
+        let mut parts = arg.splitn(2, '=');
+        let key = parts.next().unwrap_or("");
+        match (key, parts.next()) {
+            ("bingo", None) => self.min_tiles_placed = Some(7),
+            ("collapse", None) => self.collapse_blanks = true,
+            ("min_score", Some(v)) => self.min_score = parse_number(key, v)?,
+            ("min_len", Some(v)) => self.min_length = Some(parse_number(key, v)?),
+            ("max_len", Some(v)) => self.max_length = Some(parse_number(key, v)?),
+            ("min_tiles", Some(v)) => self.min_tiles_placed = Some(parse_number(key, v)?),
+            ("has", Some(v)) => self.must_include = v.to_ascii_lowercase().into_bytes(),
+            ("row", Some(v)) => self.row = Some(parse_number(key, v)?),
+            ("col", Some(v)) => self.col = Some(parse_number(key, v)?),
+            ("through", Some(v)) => {
+                let mut row_col = v.splitn(2, ',');
+                match (row_col.next(), row_col.next()) {
+                    (Some(row), Some(col)) => {
+                        self.must_use = Some(Coord::new(parse_number(key, row)?,
+                                                        parse_number(key, col)?));
+                    },
+                    _ => return Err(format!("through needs row,col, got {}", v))
+                }
+            },
+            ("dir", Some(v)) => {
+                self.direction = match v {
+                    "r" | "R" => Some(Direction::Right),
+                    "d" | "D" => Some(Direction::Down),
+                    _ => return Err(format!("dir needs r or d, got {}", v))
+                }
+            },
+            ("regex", Some(v)) => {
+                let pattern = Regex::new(v).map_err(|e| e.to_string())?;
+                self.word_pattern = Some(pattern);
+            },
+            _ => return Err(format!("Unknown restriction {}", arg))
+        }
+        Ok(())
+    }
+
+    ///
+    /// Whether a word starting at start_coord, going in dir, could be accepted
+    pub fn allows_start(&self, start_coord: Coord, dir: Direction) -> bool {
+        if let Some(d) = self.direction {
+            if d != dir {
+                return false;
+            }
+        }
+
+        if self.row.is_some() || self.col.is_some() {
+            let on_line = match dir {
+                Direction::Right => self.row == Some(start_coord.row),
+                Direction::Down => self.col == Some(start_coord.col)
+            };
+            if !on_line {
+                return false;
+            }
+        }
+
+        match self.must_use {
+            None => true,
+            Some(square) => match dir {
+                Direction::Right => square.row == start_coord.row && square.col >= start_coord.col,
+                Direction::Down => square.col == start_coord.col && square.row >= start_coord.row
+            }
+        }
+    }
+
+    ///
+    /// Whether a word of length len could still be extended by another letter
+    pub fn allows_longer_than(&self, len: usize) -> bool {
+        self.max_length.map_or(true, |max| len < max)
+    }
+
+    pub fn accepts(&self, solution: &ScrabbleSolution) -> bool {
+        let len = solution.word.len();
+        if solution.score < self.min_score
+            || self.min_length.map_or(false, |min| len < min)
+            || self.max_length.map_or(false, |max| len > max)
+            || self.min_tiles_placed.map_or(false, |min| solution.tiles_placed < min) {
+            return false;
+        }
+
+        if let Some(square) = self.must_use {
+            let (start, end, square_pos) = match solution.direction {
+                Direction::Right => (solution.start_coord.col, solution.start_coord.col + len as i32, square.col),
+                Direction::Down => (solution.start_coord.row, solution.start_coord.row + len as i32, square.row)
+            };
+            if square_pos < start || square_pos >= end {
+                return false;
+            }
+        }
+
+        let word = solution.word.to_ascii_lowercase();
+        if !self.must_include.is_empty() {
+            let mut word_letters = word.clone().into_bytes();
+            for l in self.must_include.iter() {
+                match word_letters.iter().position(|w| w == l) {
+                    Some(pos) => { word_letters.swap_remove(pos); },
+                    None => return false
+                }
+            }
+        }
+
+        self.word_pattern.as_ref().map_or(true, |pattern| pattern.is_match(&word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution(word: &str, start_coord: Coord, direction: Direction) -> ScrabbleSolution {
+        ScrabbleSolution {
+            word: String::from(word),
+            score: 10,
+            direction,
+            start_coord,
+            leave: String::new(),
+            tiles_placed: 2,
+        }
+    }
+
+    #[test]
+    fn parse_restrictions() {
+        let mut query = SolveQuery::default();
+        for arg in &["bingo", "has=QU", "through=7,8", "dir=d", "regex=^re"] {
+            query.parse_restriction(arg).unwrap();
+        }
+        assert_eq!(query.min_tiles_placed, Some(7));
+        assert_eq!(query.must_include, b"qu".to_vec());
+        assert!(query.must_use == Some(Coord::new(7, 8)));
+        assert!(query.direction == Some(Direction::Down));
+
+        assert!(query.parse_restriction("through=7").is_err());
+        assert!(query.parse_restriction("min_len=seven").is_err());
+        assert!(query.parse_restriction("colour=red").is_err());
+    }
+
+    #[test]
+    fn start_restrictions() {
+        let query = SolveQuery { must_use: Some(Coord::new(7, 7)), ..SolveQuery::default() };
+        assert!(query.allows_start(Coord::new(7, 3), Direction::Right));
+        assert!(!query.allows_start(Coord::new(7, 8), Direction::Right));
+        assert!(!query.allows_start(Coord::new(6, 3), Direction::Right));
+        assert!(query.allows_start(Coord::new(2, 7), Direction::Down));
+
+        let query = SolveQuery { row: Some(3), ..SolveQuery::default() };
+        assert!(query.allows_start(Coord::new(3, 0), Direction::Right));
+        assert!(!query.allows_start(Coord::new(3, 0), Direction::Down));
+    }
+
+    #[test]
+    fn solution_restrictions() {
+        let mut query = SolveQuery::default();
+        query.parse_restriction("through=7,7").unwrap();
+        query.parse_restriction("has=aa").unwrap();
+        assert!(query.accepts(&solution("Aha", Coord::new(7, 5), Direction::Right)));
+        assert!(!query.accepts(&solution("aha", Coord::new(7, 4), Direction::Right)));
+        assert!(!query.accepts(&solution("ah", Coord::new(7, 6), Direction::Right)));
+
+        query.parse_restriction("regex=^a.a$").unwrap();
+        assert!(query.accepts(&solution("aha", Coord::new(5, 7), Direction::Down)));
+        query.parse_restriction("min_tiles=3").unwrap();
+        assert!(!query.accepts(&solution("aha", Coord::new(5, 7), Direction::Down)));
+    }
+}
@@ -0,0 +1,227 @@
+use super::{ScrabbleSolution, SolutionSink};
+use super::leave::LeaveTable;
+use super::util::{Direction, Word};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+fn direction_rank(d: Direction) -> u8 {
+    match d {
+        Direction::Right => 0,
+        Direction::Down => 1
+    }
+}
+
+///
+/// Orders solutions best first: highest score, then alphabetically by word and position
+/// so that ties come out in the same order on every search
+pub fn compare_solutions(a: &ScrabbleSolution, b: &ScrabbleSolution) -> Ordering {
+    b.score.cmp(&a.score)
+        .then_with(|| a.word.cmp(&b.word))
+        .then_with(|| a.start_coord.row.cmp(&b.start_coord.row))
+        .then_with(|| a.start_coord.col.cmp(&b.start_coord.col))
+        .then_with(|| direction_rank(a.direction).cmp(&direction_rank(b.direction)))
+}
+
+///
+/// What the best solutions are: the ones scoring the most points,
+/// or the ones with the most points plus value of the tiles left on the rack
+#[derive(Clone, Copy)]
+pub enum SolutionRanking<'a> {
+    Score,
+    Equity(&'a LeaveTable),
+}
+
+impl<'a> SolutionRanking<'a> {
+    pub fn value(&self, solution: &ScrabbleSolution) -> f32 {
+        match self {
+            SolutionRanking::Score => solution.score as f32,
+            SolutionRanking::Equity(leaves) => solution.equity(leaves)
+        }
+    }
+}
+
+//Heap entry whose greatest element is the worst solution, so it can be evicted first
+struct RankedSolution {
+    value: f32,
+    solution: ScrabbleSolution,
+}
+
+impl PartialEq for RankedSolution {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RankedSolution {}
+
+impl PartialOrd for RankedSolution {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedSolution {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.value.partial_cmp(&self.value)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| compare_solutions(&self.solution, &other.solution))
+    }
+}
+
+///
+/// Keeps the best k solutions pushed into it, without holding on to the rest
+pub struct TopSolutions<'a> {
+    k: usize,
+    ranking: SolutionRanking<'a>,
+    heap: BinaryHeap<RankedSolution>,
+}
+
+impl<'a> TopSolutions<'a> {
+    pub fn new(k: usize) -> TopSolutions<'a> {
+        Self::ranked_by(k, SolutionRanking::Score)
+    }
+
+    pub fn ranked_by(k: usize, ranking: SolutionRanking<'a>) -> TopSolutions<'a> {
+        TopSolutions { k, ranking, heap: BinaryHeap::new() }
+    }
+
+    pub fn push(&mut self, solution: ScrabbleSolution) {
+        let ranked = RankedSolution { value: self.ranking.value(&solution), solution };
+        if self.heap.len() < self.k {
+            self.heap.push(ranked);
+        } else if let Some(worst) = self.heap.peek() {
+            if ranked < *worst {
+                self.heap.pop();
+                self.heap.push(ranked);
+            }
+        }
+    }
+
+    /// The kept solutions, best first
+    pub fn into_sorted_vec(self) -> Vec<ScrabbleSolution> {
+        self.heap.into_sorted_vec()
+            .into_iter()
+            .map(|ranked| ranked.solution)
+            .collect()
+    }
+}
+
+impl<'a> SolutionSink for TopSolutions<'a> {
+    fn accept(&mut self, solution: ScrabbleSolution) {
+        self.push(solution);
+    }
+}
+
+///
+/// Keeps only the highest scoring choice of blank letters for each word and position,
+/// handing the survivors to the inner sink (best first) once finish is called
+pub struct BestBlankDesignations<'s> {
+    inner: &'s mut dyn SolutionSink,
+    best: HashMap<(Word, i32, i32, u8), ScrabbleSolution>,
+}
+
+impl<'s> BestBlankDesignations<'s> {
+    pub fn new(inner: &'s mut dyn SolutionSink) -> BestBlankDesignations<'s> {
+        BestBlankDesignations { inner, best: HashMap::new() }
+    }
+
+    pub fn finish(self) {
+        let mut best: Vec<ScrabbleSolution> = self.best.into_iter()
+            .map(|(_, solution)| solution)
+            .collect();
+        best.sort_by(compare_solutions);
+        for solution in best {
+            if self.inner.is_done() {
+                break;
+            }
+            self.inner.accept(solution);
+        }
+    }
+}
+
+impl<'s> SolutionSink for BestBlankDesignations<'s> {
+    fn accept(&mut self, solution: ScrabbleSolution) {
+        let key = (solution.word.to_ascii_lowercase(),
+                   solution.start_coord.row,
+                   solution.start_coord.col,
+                   direction_rank(solution.direction));
+        let is_better = self.best.get(&key)
+            .map_or(true, |best| compare_solutions(&solution, best) == Ordering::Less);
+        if is_better {
+            self.best.insert(key, solution);
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.inner.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Coord;
+
+    fn solution(word: &str, score: u32) -> ScrabbleSolution {
+        solution_with_leave(word, score, "")
+    }
+
+    fn solution_with_leave(word: &str, score: u32, leave: &str) -> ScrabbleSolution {
+        ScrabbleSolution {
+            word: String::from(word),
+            score,
+            direction: Direction::Right,
+            start_coord: Coord::new(7, 7),
+            leave: String::from(leave),
+            tiles_placed: word.len() as u32,
+        }
+    }
+
+    #[test]
+    fn keeps_best_k() {
+        let mut top = TopSolutions::new(2);
+        top.push(solution("dog", 5));
+        top.push(solution("zax", 20));
+        top.push(solution("at", 2));
+        top.push(solution("cat", 5));
+
+        let words: Vec<String> = top.into_sorted_vec().into_iter().map(|s| s.word).collect();
+        assert_eq!(words, vec!["zax", "cat"]);
+    }
+
+    #[test]
+    fn keeps_best_k_by_equity() {
+        let leaves = LeaveTable::from_spec("s 8\nq -7\n").unwrap();
+        let mut top = TopSolutions::ranked_by(2, SolutionRanking::Equity(&leaves));
+        top.push(solution_with_leave("dog", 10, "q"));
+        top.push(solution_with_leave("cat", 5, "s"));
+        top.push(solution_with_leave("at", 4, ""));
+
+        let words: Vec<String> = top.into_sorted_vec().into_iter().map(|s| s.word).collect();
+        assert_eq!(words, vec!["cat", "at"]);
+    }
+
+    #[test]
+    fn collapses_blank_designations() {
+        let mut kept = Vec::new();
+        {
+            let mut sink = |solution: ScrabbleSolution| kept.push(solution);
+            let mut best = BestBlankDesignations::new(&mut sink);
+            best.accept(solution("Dog", 3));
+            best.accept(solution("dog", 5));
+            best.accept(solution("dOG", 2));
+            best.accept(solution("cat", 5));
+            best.finish();
+        }
+
+        let words: Vec<String> = kept.into_iter().map(|s| s.word).collect();
+        assert_eq!(words, vec!["cat", "dog"]);
+    }
+
+    #[test]
+    fn zero_k_keeps_nothing() {
+        let mut top = TopSolutions::new(0);
+        top.push(solution("dog", 5));
+        assert!(top.into_sorted_vec().is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use super::dictionary::DictionaryTrie;
+use super::game::{ChallengeRule, Game, GameEvent, SeededRng, RACK_SIZE};
+use super::leave::LeaveTable;
+use super::letter_bag::LetterBag;
+use super::util::{Letter, Word};
+use super::{ScrabbleBoard, ScrabbleSolution, SolutionRanking, SolveQuery};
+use std::sync::Arc;
+
+pub const DEFAULT_SIM_CANDIDATES: usize = 5;
+pub const DEFAULT_SIM_ITERATIONS: usize = 20;
+//Standard normal quantile for 95% confidence intervals
+const Z_95: f64 = 1.96;
+
+///
+/// A strategy for choosing moves in self-play
+#[derive(Clone)]
+pub enum Bot {
+    //The highest scoring play, exchanging everything when there is none
+    Greedy,
+    //The play or exchange with the most score plus leave value while tiles are left to draw,
+    //the highest scoring play after that
+    Equity { leaves: LeaveTable },
+    //Of the candidates best plays by equity, the one with the most equity less the opponent's
+    //best reply, averaged over iterations racks the opponent might hold. Once the bag is empty,
+    //score takes the place of equity
+    Simulation { leaves: LeaveTable, candidates: usize, iterations: usize },
+}
+
+pub enum BotMove {
+    Play(ScrabbleSolution),
+    //Tiles to put back, '*' for blanks
+    Exchange(Word),
+    Pass,
+}
+
+impl BotMove {
+    ///
+    /// Makes the move for the player to move in game
+    pub fn make(&self, game: &mut Game) -> Result<GameEvent, String> {
+        match self {
+            BotMove::Play(play) => game.play(play.start_coord, play.direction, &play.word),
+            BotMove::Exchange(tiles) => game.exchange(tiles),
+            BotMove::Pass => game.pass()
+        }
+    }
+}
+
+fn leaves_from(path: Option<&str>) -> Result<LeaveTable, String> {
+    match path {
+        Some(path) => LeaveTable::from_file(path),
+        None => Ok(LeaveTable::single_tiles())
+    }
+}
+
+fn top_plays(board: &ScrabbleBoard,
+             rack: &[Letter],
+             dict: &DictionaryTrie,
+             ranking: SolutionRanking,
+             n: usize) -> Vec<ScrabbleSolution> {
+    let rack = LetterBag::from_string(std::str::from_utf8(rack).unwrap());
+    board.find_top_valid_words(&rack, dict, &SolveQuery::default(), ranking, 0, n).solutions
+}
+
+///
+/// The tiles to throw back that keep the most valuable leave, with that leave's value
+fn best_exchange(rack: &[Letter], leaves: &LeaveTable) -> Option<(Word, f32)> {
+    (1..1u32 << rack.len())
+        .map(|thrown| {
+            let (mut exchanged, mut kept) = (Word::new(), Word::new());
+            for (i, &l) in rack.iter().enumerate() {
+                if thrown & (1 << i) != 0 { exchanged.push(char::from(l)) } else { kept.push(char::from(l)) }
+            }
+            let value = leaves.value(&kept);
+            (exchanged, value)
+        })
+        .fold(None, |best: Option<(Word, f32)>, (exchanged, value)| match best {
+            Some((_, best_value)) if best_value >= value => best,
+            _ => Some((exchanged, value))
+        })
+}
+
+impl Bot {
+    ///
+    /// greedy, equity[=LEAVES] or sim[=LEAVES], LEAVES being a leave table file, single tile
+    /// values by default
+    pub fn parse(spec: &str) -> Result<Bot, String> {
+        let mut parts = spec.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("greedy"), None) => Ok(Bot::Greedy),
+            (Some("equity"), path) => Ok(Bot::Equity { leaves: leaves_from(path)? }),
+            (Some("sim"), path) => Ok(Bot::Simulation {
+                leaves: leaves_from(path)?,
+                candidates: DEFAULT_SIM_CANDIDATES,
+                iterations: DEFAULT_SIM_ITERATIONS,
+            }),
+            _ => Err(format!("Unknown bot {}", spec))
+        }
+    }
+
+    ///
+    /// The move for the player to move in game, using rng for anything random
+    pub fn choose(&self, game: &Game, rng: &mut SeededRng) -> BotMove {
+        let rack = &game.players()[game.to_move()].rack;
+        let can_exchange = game.bag_len() >= RACK_SIZE;
+        let leaves = match self {
+            Bot::Greedy => None,
+            Bot::Equity { leaves } | Bot::Simulation { leaves, .. } => Some(leaves).filter(|_| game.bag_len() > 0)
+        };
+        let ranking = leaves.map_or(SolutionRanking::Score, SolutionRanking::Equity);
+        let candidates = match self {
+            Bot::Simulation { candidates, .. } => *candidates,
+            _ => 1
+        };
+        let mut plays = top_plays(game.board(), rack, game.dict(), ranking, candidates);
+
+        if let Bot::Simulation { iterations, .. } = self {
+            if plays.len() > 1 {
+                let chosen = self.simulate(game, &plays, leaves, *iterations, rng);
+                plays.swap(0, chosen);
+            }
+        }
+
+        let exchange = match leaves {
+            Some(leaves) if can_exchange => best_exchange(rack, leaves),
+            None if can_exchange => Some((String::from_utf8(rack.clone()).unwrap(), 0.0)),
+            _ => None
+        };
+        match (plays.into_iter().next(), exchange, leaves) {
+            (Some(play), Some((tiles, value)), Some(leaves)) if value > play.equity(leaves) => BotMove::Exchange(tiles),
+            (Some(play), _, _) => BotMove::Play(play),
+            (None, Some((tiles, _)), _) => BotMove::Exchange(tiles),
+            (None, None, _) => BotMove::Pass
+        }
+    }
+
+    ///
+    /// Index of the play with the best average equity, or score without leaves, less the
+    /// opponent's best reply. Every play is tried against the same opponent racks, so that the
+    /// comparison between them is fair
+    fn simulate(&self,
+                game: &Game,
+                plays: &[ScrabbleSolution],
+                leaves: Option<&LeaveTable>,
+                iterations: usize,
+                rng: &mut SeededRng) -> usize {
+        let unseen = game.unseen_tiles();
+        let opponent_racks: Vec<Vec<Letter>> = (0..iterations)
+            .map(|_| {
+                let mut tiles = unseen.clone();
+                let mut rack = Vec::new();
+                while rack.len() < RACK_SIZE && !tiles.is_empty() {
+                    rack.push(tiles.swap_remove(rng.below(tiles.len())));
+                }
+                rack
+            })
+            .collect();
+
+        let values = plays.iter().map(|play| {
+            let mut board = game.board().clone();
+            board.add_word(play.start_coord, play.direction, &play.word);
+            let replies: i32 = opponent_racks.iter()
+                .map(|rack| top_plays(&board, rack, game.dict(), SolutionRanking::Score, 1)
+                    .first()
+                    .map_or(0, |reply| reply.score as i32))
+                .sum();
+            let value = leaves.map_or(play.score as f32, |leaves| play.equity(leaves));
+            value - replies as f32 / iterations.max(1) as f32
+        });
+        values.enumerate()
+            .fold((0, f32::MIN), |best, (i, value)| if value > best.1 { (i, value) } else { best })
+            .0
+    }
+}
+
+///
+/// How one game between bots went, by the seat each bot played from
+pub struct GameRecord {
+    pub scores: Vec<i32>,
+    pub bingos: Vec<usize>,
+    //Moves made, including passes and exchanges
+    pub turns: usize,
+}
+
+///
+/// Plays a game between bots, bots[0] moving first. The same seed always gives the same game
+pub fn play_game(dict: Arc<DictionaryTrie>, bots: &[&Bot], seed: u64) -> Result<GameRecord, String> {
+    let names: Vec<String> = (1..=bots.len()).map(|i| format!("bot{}", i)).collect();
+    let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    let mut game = Game::new(dict, &names, ChallengeRule::Double, seed);
+    //Bots share one generator, separate from the one drawing tiles
+    let mut rng = SeededRng::new(!seed);
+    let mut bingos = vec![0; bots.len()];
+    let mut turns = 0;
+
+    while !game.is_over() {
+        let player = game.to_move();
+        let bot_move = bots[player].choose(&game, &mut rng);
+        if let BotMove::Play(play) = &bot_move {
+            if play.tiles_placed as usize >= RACK_SIZE {
+                bingos[player] += 1;
+            }
+        }
+        bot_move.make(&mut game)?;
+        turns += 1;
+    }
+    game.end()?;
+
+    Ok(GameRecord { scores: game.players().iter().map(|p| p.score).collect(), bingos, turns })
+}
+
+#[derive(Clone, Default)]
+pub struct BotStats {
+    pub games: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub total_score: i64,
+    pub total_spread: i64,
+    pub bingos: usize,
+}
+
+impl BotStats {
+    ///
+    /// Counting a draw as half a win
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games as f64
+    }
+
+    ///
+    /// Half the width of the 95% confidence interval for win_rate, by the normal approximation
+    pub fn win_rate_margin(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        let p = self.win_rate();
+        Z_95 * (p * (1.0 - p) / self.games as f64).sqrt()
+    }
+
+    pub fn average_score(&self) -> f64 {
+        self.total_score as f64 / self.games.max(1) as f64
+    }
+
+    pub fn average_spread(&self) -> f64 {
+        self.total_spread as f64 / self.games.max(1) as f64
+    }
+
+    pub fn bingos_per_game(&self) -> f64 {
+        self.bingos as f64 / self.games.max(1) as f64
+    }
+}
+
+///
+/// Results of games between two bots
+#[derive(Clone, Default)]
+pub struct MatchStats {
+    pub games: usize,
+    pub total_turns: usize,
+    //By bot, not by seat
+    pub bots: [BotStats; 2],
+}
+
+impl MatchStats {
+    ///
+    /// Adds a game where bot seats[i] played from seat i
+    pub fn record(&mut self, game: &GameRecord, seats: [usize; 2]) {
+        self.games += 1;
+        self.total_turns += game.turns;
+        for seat in 0..2 {
+            let (score, opponent_score) = (game.scores[seat], game.scores[1 - seat]);
+            let stats = &mut self.bots[seats[seat]];
+            stats.games += 1;
+            if score > opponent_score {
+                stats.wins += 1;
+            } else if score == opponent_score {
+                stats.draws += 1;
+            }
+            stats.total_score += score as i64;
+            stats.total_spread += (score - opponent_score) as i64;
+            stats.bingos += game.bingos[seat];
+        }
+    }
+
+    pub fn merge(&mut self, other: &MatchStats) {
+        self.games += other.games;
+        self.total_turns += other.total_turns;
+        for (stats, other) in self.bots.iter_mut().zip(other.bots.iter()) {
+            stats.games += other.games;
+            stats.wins += other.wins;
+            stats.draws += other.draws;
+            stats.total_score += other.total_score;
+            stats.total_spread += other.total_spread;
+            stats.bingos += other.bingos;
+        }
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        self.total_turns as f64 / self.games.max(1) as f64
+    }
+}
+
+///
+/// Plays the games numbered first, first + step, ... below games between bots. Games come in
+/// pairs sharing a seed, the bots swapping seats, so neither gains from the luck of the draw
+pub fn play_games(dict: Arc<DictionaryTrie>,
+                  bots: &[Bot; 2],
+                  seed: u64,
+                  games: usize,
+                  first: usize,
+                  step: usize) -> Result<MatchStats, String> {
+    let mut stats = MatchStats::default();
+    for game in (first..games).step_by(step.max(1)) {
+        let seats = if game % 2 == 0 { [0, 1] } else { [1, 0] };
+        let record = play_game(Arc::clone(&dict),
+                               &[&bots[seats[0]], &bots[seats[1]]],
+                               seed.wrapping_add((game / 2) as u64))?;
+        stats.record(&record, seats);
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bots_play_games() {
+        let dict = Arc::new(DictionaryTrie::from_scrabble_ospd());
+        let bots = [Bot::Greedy, Bot::parse("equity").unwrap()];
+
+        let record = play_game(Arc::clone(&dict), &[&bots[0], &bots[1]], 7).unwrap();
+        assert!(record.turns > 0);
+        assert!(record.scores.iter().any(|&s| s > 0));
+        let again = play_game(Arc::clone(&dict), &[&bots[0], &bots[1]], 7).unwrap();
+        assert_eq!((again.scores, again.turns), (record.scores, record.turns));
+
+        let stats = play_games(Arc::clone(&dict), &bots, 7, 2, 0, 1).unwrap();
+        assert_eq!(stats.games, 2);
+        assert_eq!(stats.bots[0].games, 2);
+        assert_eq!(stats.bots[0].wins + stats.bots[1].wins + stats.bots[0].draws, 2);
+        assert_eq!(stats.bots[0].total_spread, -stats.bots[1].total_spread);
+        assert_eq!(stats.bots[0].win_rate() + stats.bots[1].win_rate(), 1.0);
+
+        assert!(Bot::parse("random").is_err());
+        assert_eq!(best_exchange(b"qqua", &LeaveTable::single_tiles()).unwrap().0.matches('q').count(), 2);
+    }
+}
@@ -0,0 +1,79 @@
+use super::dictionary::DictionaryTrie;
+use super::probability::TileDistribution;
+use super::util::Word;
+use std::collections::BTreeMap;
+
+const VOWELS: &[u8] = b"aeiou";
+
+pub fn words_of_length(dict: &DictionaryTrie, len: usize) -> Vec<Word> {
+    dict.words().into_iter().filter(|w| w.len() == len).collect()
+}
+
+pub fn q_without_u(dict: &DictionaryTrie) -> Vec<Word> {
+    dict.words().into_iter().filter(|w| w.contains('q') && !w.contains('u')).collect()
+}
+
+pub fn with_jqxz(dict: &DictionaryTrie) -> Vec<Word> {
+    dict.words().into_iter().filter(|w| w.contains(|c| "jqxz".contains(c))).collect()
+}
+
+///
+/// Words in which vowels (not counting y) outnumber consonants
+pub fn vowel_heavy(dict: &DictionaryTrie) -> Vec<Word> {
+    dict.words()
+        .into_iter()
+        .filter(|w| 2 * w.bytes().filter(|l| VOWELS.contains(l)).count() > w.len())
+        .collect()
+}
+
+///
+/// The count words of length len most likely to be drawn from a full bag, most likely first.
+/// Equally likely words are in alphabetical order
+pub fn top_probability(dict: &DictionaryTrie, len: usize, count: usize) -> Vec<Word> {
+    let mut words = words_of_length(dict, len);
+    TileDistribution::standard().sort_by_probability(&mut words);
+    words.truncate(count);
+    words
+}
+
+///
+/// Words of dict which are not in other
+pub fn unique_to(dict: &DictionaryTrie, other: &DictionaryTrie) -> Vec<Word> {
+    dict.diff(other).removed
+}
+
+///
+/// Words grouped by their length, shortest first
+pub fn group_by_length(words: &[Word]) -> BTreeMap<usize, Vec<Word>> {
+    let mut groups: BTreeMap<usize, Vec<Word>> = BTreeMap::new();
+    for word in words {
+        groups.entry(word.len()).or_default().push(word.clone());
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_lists() {
+        let dict = DictionaryTrie::from_word_list("qi qat quit aa eau jo ox zoea cat tea ate eat");
+        assert_eq!(words_of_length(&dict, 2), vec!["aa", "jo", "ox", "qi"]);
+        assert_eq!(q_without_u(&dict), vec!["qat", "qi"]);
+        assert_eq!(with_jqxz(&dict), vec!["jo", "ox", "qat", "qi", "quit", "zoea"]);
+        assert_eq!(vowel_heavy(&dict), vec!["aa", "ate", "eat", "eau", "tea", "zoea"]);
+    }
+
+    #[test]
+    fn probability_and_uniqueness() {
+        let dict = DictionaryTrie::from_word_list("tea ate eat cat zax");
+        assert_eq!(top_probability(&dict, 3, 4), vec!["ate", "eat", "tea", "cat"]);
+
+        let other = DictionaryTrie::from_word_list("tea cat");
+        assert_eq!(unique_to(&dict, &other), vec!["ate", "eat", "zax"]);
+
+        let groups = group_by_length(&unique_to(&other, &DictionaryTrie::from_word_list("at")));
+        assert_eq!(groups.keys().cloned().collect::<Vec<usize>>(), vec![3]);
+    }
+}